@@ -1,6 +1,23 @@
-use crate::{idx::*, life::*, map::*, previsualisation::*, units::*, *};
+use crate::{events::*, i18n::tr, idx::*, life::*, map::*, previsualisation::*, units::*, *};
 use arr_macro::arr;
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct PushPhysics {
+    pub edge_damage: isize,
+    pub hazard_damage: isize,
+    pub chain_pushes: bool, // push a whole row of units like billiard balls
+}
+
+impl Default for PushPhysics {
+    fn default() -> PushPhysics {
+        PushPhysics {
+            edge_damage: 1,
+            hazard_damage: 1,
+            chain_pushes: false,
+        }
+    }
+}
+
 #[derive(PartialEq, Clone, Debug)]
 pub enum Attack {
     StickKnock,
@@ -15,13 +32,43 @@ impl Attack {
         consequences: Vec<(HexIndex, PrevisualisationItem)>,
         _map: &mut Map,
         units: &mut [Option<Unit>; 61],
-    ) {
+    ) -> Vec<GameEvent> {
+        Attack::apply_on_units(&consequences, units)
+    }
+
+    pub fn apply_on_units(
+        consequences: &[(HexIndex, PrevisualisationItem)],
+        units: &mut [Option<Unit>; 61],
+    ) -> Vec<GameEvent> {
+        let mut events = Vec::new();
         for consequence in consequences {
             match consequence {
-                (position, PrevisualisationItem::LifeChange(mut life)) => {
+                (position, PrevisualisationItem::LifeChange(life)) => {
+                    let (unit_type, before) = match &units[position.get_index()] {
+                        Some(unit) => (unit.unit_type.clone(), unit.life.get_current()),
+                        None => continue,
+                    };
+                    let mut life = life.clone();
                     life.lose_life();
+                    let after = life.get_current();
+
+                    if after < before {
+                        events.push(GameEvent::UnitDamaged(
+                            *position,
+                            unit_type.clone(),
+                            before - after,
+                        ));
+                    } else if after > before {
+                        events.push(GameEvent::UnitHealed(
+                            *position,
+                            unit_type.clone(),
+                            after - before,
+                        ));
+                    }
+
                     if life.is_dead() {
                         units[position.get_index()] = None;
+                        events.push(GameEvent::UnitDied(*position, unit_type));
                     } else {
                         units[position.get_index()].as_mut().unwrap().life = life;
                     }
@@ -34,7 +81,14 @@ impl Attack {
                         if let Some(new_position) = position.get_neighbour(&direction) {
                             if units[new_position.get_index()].is_none() {
                                 if let Some(unit) = units[position.get_index()].take() {
+                                    let unit_type = unit.unit_type.clone();
                                     units[new_position.get_index()] = Some(unit);
+                                    events.push(GameEvent::UnitPushed(
+                                        *position,
+                                        unit_type,
+                                        new_position,
+                                        direction.clone(),
+                                    ));
                                 }
                             }
                         }
@@ -42,30 +96,42 @@ impl Attack {
                 }
             }
         }
+        events
     }
 
     pub fn get_description(&self) -> &'static str {
         match self {
-            Attack::StickKnock => "Hit an adjacent unit (1 damage) and push it away.",
-            Attack::VolleyOfArrows => "Shoot arrows in one direction. The first ennemy on that direction will be damaged (1 damage) and pushed away.",
-            Attack::OffensiveSwordFight => "Attack adjacent unit using sword (2 damage) and pull it (1 damage for both units).",
-            Attack::DefensiveSwordFight => "Attack adjacent unit using sword (2 damage) and push it away.",
-            Attack::Heal => "Restore 1 LP. The healed unit will be restored at least to the third of the max LPs.",
+            Attack::StickKnock => tr("attack.stick_knock.description"),
+            Attack::VolleyOfArrows => tr("attack.volley_of_arrows.description"),
+            Attack::OffensiveSwordFight => tr("attack.offensive_sword_fight.description"),
+            Attack::DefensiveSwordFight => tr("attack.defensive_sword_fight.description"),
+            Attack::Heal => tr("attack.heal.description"),
         }
     }
 
-    pub fn _get_name(&self) -> &'static str {
+    pub fn get_name(&self) -> &'static str {
         match self {
-            Attack::StickKnock => "Stick Knock",
-            Attack::VolleyOfArrows => "Volley of Arrows",
-            Attack::OffensiveSwordFight => "Offensive Sword Fight",
-            Attack::DefensiveSwordFight => "Defensive Sword Fight",
-            Attack::Heal => "Heal",
+            Attack::StickKnock => tr("attack.stick_knock"),
+            Attack::VolleyOfArrows => tr("attack.volley_of_arrows"),
+            Attack::OffensiveSwordFight => tr("attack.offensive_sword_fight"),
+            Attack::DefensiveSwordFight => tr("attack.defensive_sword_fight"),
+            Attack::Heal => tr("attack.heal"),
         }
     }
 
-    pub fn _get_icon_idx(&self) -> usize {
-        unimplemented!();
+    // index of the icon in atlas::ICONS
+    pub fn get_icon_idx(&self) -> usize {
+        match self {
+            Attack::StickKnock => 0,
+            Attack::VolleyOfArrows => 1,
+            Attack::OffensiveSwordFight => 2,
+            Attack::DefensiveSwordFight => 3,
+            Attack::Heal => 4,
+        }
+    }
+
+    pub fn get_icon_name(&self) -> &'static str {
+        crate::atlas::ICONS[self.get_icon_idx()]
     }
 
     pub fn _can_be_used_by_unit(&self, unit: &UnitType) -> bool {
@@ -208,150 +274,173 @@ impl Attack {
         third
     }
 
-    pub fn get_consequences(
-        &self,
-        _map: &Map,
+    pub fn get_push_consequences(
+        physics: &PushPhysics,
         units: &[Option<Unit>; 61],
-        position: &HexIndex,
         target: &HexIndex,
+        direction: Direction,
+        damage: isize,
+        is_hazard: &dyn Fn(&HexIndex) -> bool,
     ) -> Vec<(HexIndex, PrevisualisationItem)> {
-        match self {
-            Attack::StickKnock => {
-                for direction in Direction::iter() {
-                    if Some(*target) == position.get_neighbour(&direction) {
-                        let mut consequences = Vec::new();
-                        if let Some(life) = units[target.get_index()].as_ref().map(|u| &u.life) {
-                            if let Some((other_pos, Some(other_life))) =
-                                target.get_neighbour(&direction).map(|new_pos| {
-                                    (
-                                        new_pos,
-                                        units[new_pos.get_index()].as_ref().map(|u| &u.life),
-                                    )
-                                })
-                            {
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::LifeChange(life.previsualise_loss(2)),
-                                ));
+        let life = match units[target.get_index()].as_ref() {
+            Some(unit) => &unit.life,
+            None => return vec![(*target, PrevisualisationItem::PushArrow(direction, false))],
+        };
+
+        let mut consequences = Vec::new();
+        match target.get_neighbour(&direction) {
+            None => {
+                // the unit is slammed into the edge of the board
+                consequences.push((
+                    *target,
+                    PrevisualisationItem::LifeChange(
+                        life.previsualise_loss(damage + physics.edge_damage),
+                    ),
+                ));
+                consequences.push((*target, PrevisualisationItem::PushArrow(direction, true)));
+            }
+            Some(next) if units[next.get_index()].is_none() => {
+                let damage = if is_hazard(&next) {
+                    damage + physics.hazard_damage
+                } else {
+                    damage
+                };
+                consequences.push((
+                    *target,
+                    PrevisualisationItem::LifeChange(life.previsualise_loss(damage)),
+                ));
+                consequences.push((*target, PrevisualisationItem::PushArrow(direction, false)));
+            }
+            Some(next) => {
+                // the row of units standing behind the target
+                let mut row = vec![next];
+                let mut free_hex = None;
+                while let Some(index) = row[row.len() - 1].get_neighbour(&direction) {
+                    if units[index.get_index()].is_none() {
+                        free_hex = Some(index);
+                        break;
+                    }
+                    row.push(index);
+                }
+
+                match free_hex {
+                    Some(free_hex) if physics.chain_pushes => {
+                        let damage = if is_hazard(&next) {
+                            damage + physics.hazard_damage
+                        } else {
+                            damage
+                        };
+                        consequences.push((
+                            *target,
+                            PrevisualisationItem::LifeChange(life.previsualise_loss(damage)),
+                        ));
+                        for (idx, position) in row.iter().enumerate() {
+                            let destination = row.get(idx + 1).unwrap_or(&free_hex);
+                            if is_hazard(destination) {
+                                let life = &units[position.get_index()].as_ref().unwrap().life;
                                 consequences.push((
-                                    other_pos,
+                                    *position,
                                     PrevisualisationItem::LifeChange(
-                                        other_life.previsualise_loss(1),
+                                        life.previsualise_loss(physics.hazard_damage),
                                     ),
                                 ));
+                            }
+                        }
+
+                        // the farthest unit has to move first to free the hex behind it
+                        for position in row.iter().rev() {
+                            consequences.push((
+                                *position,
+                                PrevisualisationItem::PushArrow(direction.clone(), false),
+                            ));
+                        }
+                        consequences
+                            .push((*target, PrevisualisationItem::PushArrow(direction, false)));
+                    }
+                    _ => {
+                        // with chain pushes, a row against the edge of the board hits it too
+                        let impact = match free_hex {
+                            None if physics.chain_pushes => 1 + physics.edge_damage,
+                            _ => 1,
+                        };
+                        let other_life = &units[next.get_index()].as_ref().unwrap().life;
+                        consequences.push((
+                            *target,
+                            PrevisualisationItem::LifeChange(
+                                life.previsualise_loss(damage + impact),
+                            ),
+                        ));
+                        consequences.push((
+                            next,
+                            PrevisualisationItem::LifeChange(other_life.previsualise_loss(impact)),
+                        ));
+                        if physics.chain_pushes {
+                            for position in row.iter().rev() {
                                 consequences.push((
-                                    *target,
-                                    PrevisualisationItem::PushArrow(direction, true),
-                                ));
-                            } else {
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::LifeChange(life.previsualise_loss(1)),
-                                ));
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::PushArrow(direction, false),
+                                    *position,
+                                    PrevisualisationItem::PushArrow(direction.clone(), true),
                                 ));
                             }
-                        } else {
-                            consequences
-                                .push((*target, PrevisualisationItem::PushArrow(direction, false)));
                         }
-                        return consequences;
+                        consequences
+                            .push((*target, PrevisualisationItem::PushArrow(direction, true)));
+                    }
+                }
+            }
+        }
+
+        consequences
+    }
+
+    pub fn get_consequences(
+        &self,
+        map: &Map,
+        units: &[Option<Unit>; 61],
+        position: &HexIndex,
+        target: &HexIndex,
+    ) -> Vec<(HexIndex, PrevisualisationItem)> {
+        let is_hazard = |index: &HexIndex| map[index].is_hazard();
+
+        match self {
+            Attack::StickKnock => {
+                for direction in Direction::iter() {
+                    if Some(*target) == position.get_neighbour(&direction) {
+                        return Attack::get_push_consequences(
+                            &map.push_physics,
+                            units,
+                            target,
+                            direction,
+                            1,
+                            &is_hazard,
+                        );
                     }
                 }
             }
             Attack::DefensiveSwordFight => {
                 for direction in Direction::iter() {
                     if Some(*target) == position.get_neighbour(&direction) {
-                        let mut consequences = Vec::new();
-                        if let Some(life) = units[target.get_index()].as_ref().map(|u| &u.life) {
-                            if let Some((other_pos, Some(other_life))) =
-                                target.get_neighbour(&direction).map(|new_pos| {
-                                    (
-                                        new_pos,
-                                        units[new_pos.get_index()].as_ref().map(|u| &u.life),
-                                    )
-                                })
-                            {
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::LifeChange(life.previsualise_loss(3)),
-                                ));
-                                consequences.push((
-                                    other_pos,
-                                    PrevisualisationItem::LifeChange(
-                                        other_life.previsualise_loss(1),
-                                    ),
-                                ));
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::PushArrow(direction, true),
-                                ));
-                            } else {
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::LifeChange(life.previsualise_loss(2)),
-                                ));
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::PushArrow(direction, false),
-                                ));
-                            }
-                        } else {
-                            consequences
-                                .push((*target, PrevisualisationItem::PushArrow(direction, false)));
-                        }
-
-                        return consequences;
+                        return Attack::get_push_consequences(
+                            &map.push_physics,
+                            units,
+                            target,
+                            direction,
+                            2,
+                            &is_hazard,
+                        );
                     }
                 }
             }
             Attack::OffensiveSwordFight => {
                 for direction in Direction::iter() {
                     if Some(*target) == position.get_neighbour(&direction) {
-                        let mut consequences = Vec::new();
-                        if let Some(life) = units[target.get_index()].as_ref().map(|u| &u.life) {
-                            if let Some((other_pos, Some(other_life))) =
-                                target.get_neighbour(&!direction.clone()).map(|new_pos| {
-                                    (
-                                        new_pos,
-                                        units[new_pos.get_index()].as_ref().map(|u| &u.life),
-                                    )
-                                })
-                            {
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::LifeChange(life.previsualise_loss(3)),
-                                ));
-                                consequences.push((
-                                    other_pos,
-                                    PrevisualisationItem::LifeChange(
-                                        other_life.previsualise_loss(1),
-                                    ),
-                                ));
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::PushArrow(!direction, true),
-                                ));
-                            } else {
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::LifeChange(life.previsualise_loss(2)),
-                                ));
-                                consequences.push((
-                                    *target,
-                                    PrevisualisationItem::PushArrow(!direction, false),
-                                ));
-                            }
-                        } else {
-                            consequences.push((
-                                *target,
-                                PrevisualisationItem::PushArrow(!direction, false),
-                            ));
-                        }
-
-                        return consequences;
+                        return Attack::get_push_consequences(
+                            &map.push_physics,
+                            units,
+                            target,
+                            !direction,
+                            2,
+                            &is_hazard,
+                        );
                     }
                 }
             }
@@ -399,42 +488,15 @@ impl Attack {
                 }
 
                 let mut consequences = Vec::new();
-                if let Some(life) = units[final_target.unwrap().get_index()]
-                    .as_ref()
-                    .map(|u| &u.life)
-                {
-                    if let Some((other_pos, Some(other_life))) = final_target
-                        .unwrap()
-                        .get_neighbour(&final_direction.clone().unwrap())
-                        .map(|new_pos| {
-                            (
-                                new_pos,
-                                units[new_pos.get_index()].as_ref().map(|u| &u.life),
-                            )
-                        })
-                    {
-                        consequences.push((
-                            final_target.unwrap(),
-                            PrevisualisationItem::LifeChange(life.previsualise_loss(3)),
-                        ));
-                        consequences.push((
-                            other_pos,
-                            PrevisualisationItem::LifeChange(other_life.previsualise_loss(1)),
-                        ));
-                        consequences.push((
-                            final_target.unwrap(),
-                            PrevisualisationItem::PushArrow(final_direction.unwrap(), true),
-                        ));
-                    } else {
-                        consequences.push((
-                            final_target.unwrap(),
-                            PrevisualisationItem::LifeChange(life.previsualise_loss(2)),
-                        ));
-                        consequences.push((
-                            final_target.unwrap(),
-                            PrevisualisationItem::PushArrow(final_direction.unwrap(), false),
-                        ));
-                    }
+                if units[final_target.unwrap().get_index()].is_some() {
+                    consequences.append(&mut Attack::get_push_consequences(
+                        &map.push_physics,
+                        units,
+                        &final_target.unwrap(),
+                        final_direction.unwrap(),
+                        2,
+                        &is_hazard,
+                    ));
                 }
 
                 consequences.push((
@@ -498,4 +560,204 @@ mod test {
 
         assert_eq!(compiled.len(), 4);
     }
+
+    #[test]
+    fn chain_pushes() {
+        let mut units: [Option<Unit>; 61] = arr!(None; 61);
+        units[27] = Some(Unit::new(UnitType::Knight));
+        units[28] = Some(Unit::new(UnitType::Barbarian));
+        let target: HexIndex = 27.try_into().unwrap();
+        let mut physics = PushPhysics::default();
+
+        let no_hazard = |_: &HexIndex| false;
+
+        let blocked = Attack::get_push_consequences(
+            &physics,
+            &units,
+            &target,
+            Direction::Right,
+            1,
+            &no_hazard,
+        );
+        for (_position, item) in &blocked {
+            if let PrevisualisationItem::PushArrow(_direction, cancelled) = item {
+                assert!(*cancelled);
+            }
+        }
+
+        physics.chain_pushes = true;
+        let chained = Attack::get_push_consequences(
+            &physics,
+            &units,
+            &target,
+            Direction::Right,
+            1,
+            &no_hazard,
+        );
+        let arrows: Vec<usize> = chained
+            .iter()
+            .filter_map(|(position, item)| match item {
+                PrevisualisationItem::PushArrow(_direction, false) => Some(position.get_index()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(arrows, vec![28, 27]);
+    }
+
+    #[test]
+    fn edge_impact() {
+        let mut units: [Option<Unit>; 61] = arr!(None; 61);
+        units[34] = Some(Unit::new(UnitType::Knight));
+        let target: HexIndex = 34.try_into().unwrap();
+
+        let consequences = Attack::get_push_consequences(
+            &PushPhysics::default(),
+            &units,
+            &target,
+            Direction::Right,
+            1,
+            &|_| false,
+        );
+        assert_eq!(consequences.len(), 2);
+        if let (_position, PrevisualisationItem::LifeChange(life)) = &consequences[0] {
+            assert_eq!(life.loss, 2);
+        } else {
+            panic!("the unit should be damaged by the edge");
+        }
+    }
+
+    #[test]
+    fn hazards() {
+        let mut units: [Option<Unit>; 61] = arr!(None; 61);
+        units[27] = Some(Unit::new(UnitType::Knight));
+        let target: HexIndex = 27.try_into().unwrap();
+        let mut physics = PushPhysics::default();
+        physics.hazard_damage = 2;
+        let is_hazard = |index: &HexIndex| index.get_index() >= 29;
+
+        let losses = |consequences: Vec<(HexIndex, PrevisualisationItem)>| -> Vec<(usize, isize)> {
+            consequences
+                .iter()
+                .filter_map(|(position, item)| match item {
+                    PrevisualisationItem::LifeChange(life) => {
+                        Some((position.get_index(), life.loss))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // pushed on a safe tile then in a hazard
+        let consequences = Attack::get_push_consequences(
+            &physics,
+            &units,
+            &target,
+            Direction::Right,
+            1,
+            &is_hazard,
+        );
+        assert_eq!(losses(consequences), vec![(27, 1)]);
+        units.swap(27, 28);
+        let target: HexIndex = 28.try_into().unwrap();
+        let consequences = Attack::get_push_consequences(
+            &physics,
+            &units,
+            &target,
+            Direction::Right,
+            1,
+            &is_hazard,
+        );
+        assert_eq!(losses(consequences), vec![(28, 3)]);
+
+        // only the unit pushed in the hazard of a row is hurt by it
+        physics.chain_pushes = true;
+        units[27] = Some(Unit::new(UnitType::Archer));
+        let target: HexIndex = 27.try_into().unwrap();
+        let consequences = Attack::get_push_consequences(
+            &physics,
+            &units,
+            &target,
+            Direction::Right,
+            1,
+            &is_hazard,
+        );
+        assert_eq!(losses(consequences), vec![(27, 1), (28, 2)]);
+    }
+
+    #[test]
+    fn row_against_the_edge() {
+        let mut units: [Option<Unit>; 61] = arr!(None; 61);
+        units[33] = Some(Unit::new(UnitType::Knight));
+        units[34] = Some(Unit::new(UnitType::Knight));
+        let target: HexIndex = 33.try_into().unwrap();
+        let mut physics = PushPhysics::default();
+        physics.edge_damage = 2;
+        let no_hazard = |_: &HexIndex| false;
+        let losses = |physics: &PushPhysics| -> Vec<(usize, isize)> {
+            Attack::get_push_consequences(physics, &units, &target, Direction::Right, 1, &no_hazard)
+                .iter()
+                .filter_map(|(position, item)| match item {
+                    PrevisualisationItem::LifeChange(life) => {
+                        Some((position.get_index(), life.loss))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // without chain pushes the target only hits the unit behind it
+        assert_eq!(losses(&physics), vec![(33, 2), (34, 1)]);
+        physics.chain_pushes = true;
+        assert_eq!(losses(&physics), vec![(33, 4), (34, 3)]);
+    }
+
+    #[test]
+    fn applying_consequences() {
+        let mut units: [Option<Unit>; 61] = arr!(None; 61);
+        units[27] = Some(Unit::new(UnitType::Archer));
+        let position: HexIndex = 27.try_into().unwrap();
+        let life = units[27].as_ref().unwrap().life.previsualise_loss(1);
+
+        let events = Attack::apply_on_units(
+            &[
+                (position, PrevisualisationItem::LifeChange(life)),
+                (
+                    position,
+                    PrevisualisationItem::PushArrow(Direction::Right, false),
+                ),
+            ],
+            &mut units,
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::UnitDamaged(position, UnitType::Archer, 1),
+                GameEvent::UnitPushed(
+                    position,
+                    UnitType::Archer,
+                    28.try_into().unwrap(),
+                    Direction::Right,
+                ),
+            ]
+        );
+        assert!(units[27].is_none());
+        assert!(units[28].is_some());
+    }
+
+    #[test]
+    fn icons() {
+        let attacks = [
+            Attack::StickKnock,
+            Attack::VolleyOfArrows,
+            Attack::OffensiveSwordFight,
+            Attack::DefensiveSwordFight,
+            Attack::Heal,
+        ];
+        for (idx, attack) in attacks.iter().enumerate() {
+            assert_eq!(attack.get_icon_idx(), idx);
+        }
+        assert_eq!(attacks.len(), crate::atlas::ICONS.len());
+        assert_eq!(Attack::Heal.get_icon_name(), "heal");
+    }
 }
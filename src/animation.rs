@@ -0,0 +1,186 @@
+use crate::{events::*, idx::HexIndex, map::*, *};
+
+const STEP_DURATION: f64 = 150.0;
+const HIT_DURATION: f64 = 700.0;
+const FLASH_PERIOD: f64 = 100.0;
+
+pub enum AnimationKind {
+    Move(Vec<HexIndex>), // every hex crossed, including the starting one
+    Hit(isize),          // life change, negative when the unit is healed
+}
+
+pub struct Animation {
+    unit: HexIndex, // current position of the animated unit on the board
+    hex: HexIndex,  // where the animation takes place
+    kind: AnimationKind,
+    start: f64,
+    duration: f64,
+}
+
+#[derive(Default)]
+pub struct Timeline {
+    animations: Vec<Animation>,
+    clock: f64,
+    step_start: f64,
+}
+
+impl Timeline {
+    pub fn update(&mut self, delta: f64) {
+        self.clock += delta;
+        let clock = self.clock;
+        self.animations.retain(|a| a.start + a.duration > clock);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.animations.is_empty()
+    }
+
+    fn get_end(&self) -> f64 {
+        self.animations
+            .iter()
+            .map(|a| a.start + a.duration)
+            .fold(self.clock, f64::max)
+    }
+
+    fn push(&mut self, unit: HexIndex, hex: HexIndex, kind: AnimationKind, duration: f64) {
+        self.animations.push(Animation {
+            unit,
+            hex,
+            kind,
+            start: self.step_start.max(self.clock),
+            duration,
+        });
+    }
+
+    // the animations of a unit follow it when it is moved on the board
+    fn follow(&mut self, from: HexIndex, to: HexIndex) {
+        for animation in self.animations.iter_mut().filter(|a| a.unit == from) {
+            animation.unit = to;
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::UnitMoved(from, _unit_type, to, path) => {
+                self.step_start = self.get_end();
+                self.follow(*from, *to);
+                let mut hexes = vec![*from];
+                hexes.extend_from_slice(path);
+                self.push(
+                    *to,
+                    *to,
+                    AnimationKind::Move(hexes),
+                    STEP_DURATION * path.len() as f64,
+                );
+            }
+            GameEvent::UnitAttacked(..) | GameEvent::TurnStarted(_) => {
+                self.step_start = self.get_end();
+            }
+            GameEvent::UnitPushed(from, _unit_type, to, _direction) => {
+                self.follow(*from, *to);
+                self.push(
+                    *to,
+                    *to,
+                    AnimationKind::Move(vec![*from, *to]),
+                    STEP_DURATION,
+                );
+            }
+            GameEvent::UnitDamaged(position, _unit_type, damage) => {
+                self.push(
+                    *position,
+                    *position,
+                    AnimationKind::Hit(*damage as isize),
+                    HIT_DURATION,
+                );
+            }
+            GameEvent::UnitHealed(position, _unit_type, amount) => {
+                self.push(
+                    *position,
+                    *position,
+                    AnimationKind::Hit(-(*amount as isize)),
+                    HIT_DURATION,
+                );
+            }
+            GameEvent::UnitDied(_position, _unit_type) => (),
+        }
+    }
+
+    // the offset between the displayed position of a unit and its position on the board
+    pub fn get_unit_offset(&self, unit: &HexIndex) -> (isize, isize) {
+        let animation = self
+            .animations
+            .iter()
+            .find(|a| a.unit == *unit && matches!(a.kind, AnimationKind::Move(_)));
+
+        if let Some(Animation {
+            kind: AnimationKind::Move(path),
+            start,
+            ..
+        }) = animation
+        {
+            let progress = ((self.clock - start) / STEP_DURATION).max(0.0);
+            let step = progress as usize;
+            let (x, y) = if step + 1 >= path.len() {
+                let (x, y) = path[path.len() - 1].get_canvas_coords();
+                (x as f64, y as f64)
+            } else {
+                let (x1, y1) = path[step].get_canvas_coords();
+                let (x2, y2) = path[step + 1].get_canvas_coords();
+                let ratio = progress - step as f64;
+                (
+                    x1 as f64 + (x2 as f64 - x1 as f64) * ratio,
+                    y1 as f64 + (y2 as f64 - y1 as f64) * ratio,
+                )
+            };
+            let (final_x, final_y) = unit.get_canvas_coords();
+            (x as isize - final_x as isize, y as isize - final_y as isize)
+        } else {
+            (0, 0)
+        }
+    }
+
+    pub fn is_flashing(&self, unit: &HexIndex) -> bool {
+        self.animations.iter().any(|a| {
+            a.unit == *unit
+                && self.clock >= a.start
+                && match a.kind {
+                    AnimationKind::Hit(damage) => {
+                        damage > 0 && ((self.clock - a.start) / FLASH_PERIOD) as usize % 2 == 0
+                    }
+                    _ => false,
+                }
+        })
+    }
+
+    // draw floating damage numbers
+    pub fn draw_on_canvas(&self, canvas: &mut Canvas, data: &DrawingData) {
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.set_font(&format!("bold {}px Arial", (60.0 * data.factor) as usize));
+        context.set_text_align("center");
+
+        for animation in self.animations.iter().filter(|a| self.clock >= a.start) {
+            if let AnimationKind::Hit(damage) = animation.kind {
+                let progress = (self.clock - animation.start) / animation.duration;
+                let (x, y) = animation.hex.get_canvas_coords();
+                let (x, y) = data.viewport.board_to_screen(
+                    x as isize + 128,
+                    y as isize + 140 - (progress * 100.0) as isize,
+                );
+
+                let (text, color) = if damage > 0 {
+                    (format!("-{}", damage), "255, 60, 30")
+                } else {
+                    (format!("+{}", -damage), "0, 255, 100")
+                };
+                context.set_fill_style(&JsValue::from_str(&format!(
+                    "rgba({}, {})",
+                    color,
+                    1.0 - progress
+                )));
+                context.fill_text(&text, x as f64, y as f64).unwrap();
+            }
+        }
+
+        context.set_text_align("start");
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{loader::*, scenario::Scenario};
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::{canvas::Canvas, font::Font, image::Image};
+use web_sys::HtmlAudioElement;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Category {
+    Tile,
+    Underground,
+    Overlay,
+    Unit,
+    Atlas,
+    Font,
+    Sound,
+    Scenario,
+}
+
+impl Category {
+    pub fn get_kind(self) -> Kind {
+        match self {
+            Category::Tile
+            | Category::Underground
+            | Category::Overlay
+            | Category::Unit
+            | Category::Atlas => Kind::Image,
+            Category::Font => Kind::Font,
+            Category::Sound => Kind::Sound,
+            Category::Scenario => Kind::Scenarios,
+        }
+    }
+}
+
+// a named asset, the game cannot start without the required ones
+pub struct Asset {
+    pub category: Category,
+    pub name: &'static str,
+    pub paths: &'static [&'static str], // one path per variant
+    pub weight: usize,                  // of each path in the progress bar
+    pub required: bool,
+}
+
+// every asset of the game, missing optional assets are replaced by a placeholder
+pub const ASSETS: [Asset; 23] = [
+    Asset {
+        category: Category::Tile,
+        name: "grassy_plain",
+        paths: &[
+            "textures/plains/grassy_plain1",
+            "textures/plains/grassy_plain2",
+            "textures/plains/grassy_plain3",
+            "textures/plains/grassy_plain4",
+        ],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Tile,
+        name: "forest",
+        paths: &[
+            "textures/forest/forest1",
+            "textures/forest/forest2",
+            "textures/forest/forest3",
+            "textures/forest/forest4",
+        ],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Tile,
+        name: "plain",
+        paths: &[
+            "textures/plains/plain1",
+            "textures/plains/plain2",
+            "textures/plains/plain3",
+            "textures/plains/plain4",
+        ],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Tile,
+        name: "rocks",
+        paths: &["textures/rocks/rocks1", "textures/rocks/rocks2"],
+        weight: 1,
+        required: false,
+    },
+    Asset {
+        category: Category::Underground,
+        name: "dirt",
+        paths: &["textures/underground/dirt"],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Overlay,
+        name: "shadow",
+        paths: &["textures/shadow.png"],
+        weight: 1,
+        required: false,
+    },
+    Asset {
+        category: Category::Overlay,
+        name: "red",
+        paths: &["textures/red.png"],
+        weight: 1,
+        required: false,
+    },
+    Asset {
+        category: Category::Unit,
+        name: "archer",
+        paths: &["units/archer.png"],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Unit,
+        name: "knight",
+        paths: &["units/knight.png"],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Unit,
+        name: "scout",
+        paths: &["units/scout.png"],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Unit,
+        name: "barbarian",
+        paths: &["units/barbarian.png"],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Unit,
+        name: "barbarian_variant",
+        paths: &["units/barbarian2.png"],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Unit,
+        name: "armored_barbarian",
+        paths: &["units/barbarian3.png"],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Unit,
+        name: "barbarian_lord_of_death",
+        paths: &["units/barbarian4.png"],
+        weight: 1,
+        required: true,
+    },
+    Asset {
+        category: Category::Atlas,
+        name: "icons",
+        paths: &["textures/icons.png"],
+        weight: 1,
+        required: false,
+    },
+    Asset {
+        category: Category::Font,
+        name: "arial",
+        paths: &["fonts/arial.woff2"],
+        weight: 2,
+        required: false,
+    },
+    Asset {
+        category: Category::Scenario,
+        name: "scenarios",
+        paths: &["data/scenarios.txt"],
+        weight: 1,
+        required: false,
+    },
+    Asset {
+        category: Category::Sound,
+        name: "footsteps",
+        paths: &["sounds/footsteps.ogg"],
+        weight: 2,
+        required: false,
+    },
+    Asset {
+        category: Category::Sound,
+        name: "sword_clash",
+        paths: &["sounds/sword_clash.ogg"],
+        weight: 2,
+        required: false,
+    },
+    Asset {
+        category: Category::Sound,
+        name: "arrow_volley",
+        paths: &["sounds/arrow_volley.ogg"],
+        weight: 2,
+        required: false,
+    },
+    Asset {
+        category: Category::Sound,
+        name: "death",
+        paths: &["sounds/death.ogg"],
+        weight: 2,
+        required: false,
+    },
+    Asset {
+        category: Category::Sound,
+        name: "turn_start",
+        paths: &["sounds/turn_start.ogg"],
+        weight: 2,
+        required: false,
+    },
+    Asset {
+        category: Category::Sound,
+        name: "ambient",
+        paths: &["sounds/ambient.ogg"],
+        weight: 10,
+        required: false,
+    },
+];
+
+pub struct Registry<T> {
+    entries: Vec<(Category, &'static str, Vec<T>)>,
+}
+
+pub type TextureRegistry = Registry<Image>;
+
+// every loaded asset, sorted by type
+pub struct Assets {
+    pub textures: TextureRegistry,
+    pub fonts: Registry<Font>,
+    pub sounds: Registry<HtmlAudioElement>,
+    pub scenarios: Registry<Vec<Scenario>>,
+}
+
+impl<T> Registry<T> {
+    // values: one value per path of the assets of this kind, in the order of the manifest
+    pub fn from_manifest(manifest: &[Asset], kind: Kind, values: Vec<T>) -> Registry<T> {
+        let mut values = values.into_iter();
+        let entries = manifest
+            .iter()
+            .filter(|asset| asset.category.get_kind() == kind)
+            .map(|asset| {
+                (
+                    asset.category,
+                    asset.name,
+                    values.by_ref().take(asset.paths.len()).collect(),
+                )
+            })
+            .collect();
+        Registry { entries }
+    }
+
+    // the variant number wraps around when it is bigger than the number of variants
+    pub fn get_variant(&self, category: Category, name: &str, variant: usize) -> &T {
+        let variants = self
+            .entries
+            .iter()
+            .find(|(c, n, variants)| *c == category && *n == name && !variants.is_empty())
+            .map(|(_category, _name, variants)| variants)
+            .unwrap_or_else(|| panic!("no asset named {} in {:?}", name, category));
+        &variants[variant % variants.len()]
+    }
+
+    pub fn get(&self, category: Category, name: &str) -> &T {
+        self.get_variant(category, name, 0)
+    }
+}
+
+impl Assets {
+    // fails when a required asset cannot be loaded
+    pub async fn load(canvas: &mut Canvas) -> Result<Assets, JsValue> {
+        let requests = ASSETS
+            .iter()
+            .flat_map(|asset| {
+                asset.paths.iter().map(move |path| LoadRequest {
+                    path,
+                    kind: asset.category.get_kind(),
+                    weight: asset.weight,
+                    required: asset.required,
+                })
+            })
+            .collect();
+
+        let (mut textures, mut fonts, mut sounds, mut scenarios) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for asset in load_assets(requests, canvas).await? {
+            match asset {
+                Loaded::Image(image) => textures.push(image),
+                Loaded::Font(font) => fonts.push(font),
+                Loaded::Sound(sound) => sounds.push(sound),
+                Loaded::Scenarios(decoded) => scenarios.push(decoded),
+            }
+        }
+
+        Ok(Assets {
+            textures: Registry::from_manifest(&ASSETS, Kind::Image, textures),
+            fonts: Registry::from_manifest(&ASSETS, Kind::Font, fonts),
+            sounds: Registry::from_manifest(&ASSETS, Kind::Sound, sounds),
+            scenarios: Registry::from_manifest(&ASSETS, Kind::Scenarios, scenarios),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lookups() {
+        let count = ASSETS
+            .iter()
+            .filter(|asset| asset.category.get_kind() == Kind::Image)
+            .map(|asset| asset.paths.len())
+            .sum();
+        let registry = Registry::from_manifest(&ASSETS, Kind::Image, (0..count).collect());
+
+        assert_eq!(*registry.get(Category::Tile, "grassy_plain"), 0);
+        assert_eq!(*registry.get_variant(Category::Tile, "forest", 2), 6);
+        assert_eq!(*registry.get_variant(Category::Tile, "forest", 5), 5);
+        assert_eq!(*registry.get_variant(Category::Tile, "rocks", 3), 13);
+        assert_eq!(*registry.get(Category::Overlay, "red"), 16);
+        assert_eq!(*registry.get(Category::Atlas, "icons"), count - 1);
+
+        assert_eq!(Category::Unit.get_kind(), Kind::Image);
+        assert_eq!(Category::Scenario.get_kind(), Kind::Scenarios);
+    }
+}
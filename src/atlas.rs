@@ -0,0 +1,79 @@
+use wasm_game_lib::graphics::{canvas::*, image::*};
+
+// names of the icons packed in textures/icons.png, row by row
+pub const ICONS: [&str; 5] = [
+    "stick_knock",
+    "volley_of_arrows",
+    "offensive_sword_fight",
+    "defensive_sword_fight",
+    "heal",
+];
+pub const ICON_SIZE: f64 = 64.0;
+const ICON_COLUMNS: usize = 4;
+
+// a grid of square sprites packed in one image
+pub struct Atlas<'a> {
+    image: &'a Image,
+    names: &'static [&'static str],
+    sprite_size: f64,
+    columns: usize,
+}
+
+impl<'a> Atlas<'a> {
+    pub fn new(
+        image: &'a Image,
+        names: &'static [&'static str],
+        sprite_size: f64,
+        columns: usize,
+    ) -> Atlas<'a> {
+        Atlas {
+            image,
+            names,
+            sprite_size,
+            columns,
+        }
+    }
+
+    pub fn new_icons(image: &'a Image) -> Atlas<'a> {
+        Atlas::new(image, &ICONS, ICON_SIZE, ICON_COLUMNS)
+    }
+
+    pub fn get_index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| *n == name)
+    }
+
+    // (x, y, width, height) of the sprite in the image
+    pub fn get_rect(&self, name: &str) -> Option<(f64, f64, f64, f64)> {
+        let index = self.get_index(name)?;
+        Some((
+            (index % self.columns) as f64 * self.sprite_size,
+            (index / self.columns) as f64 * self.sprite_size,
+            self.sprite_size,
+            self.sprite_size,
+        ))
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas, name: &str, coords: (f64, f64), size: f64) {
+        let (x, y, width, height) = match self.get_rect(name) {
+            Some(rect) => rect,
+            None => {
+                log!("no sprite named {} in the atlas", name);
+                return;
+            }
+        };
+        canvas
+            .get_2d_canvas_rendering_context()
+            .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                self.image.get_html_element(),
+                x,
+                y,
+                width,
+                height,
+                coords.0,
+                coords.1,
+                size,
+                size,
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{actions::Attack, assets::*, events::GameEvent, input::Intent};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlAudioElement;
+
+const MUSIC: &str = "ambient";
+
+// set from the settings
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Mixer {
+    Master,
+    Music,
+    Effects,
+}
+
+pub trait AudioBackend {
+    // volume: from 0 to 1
+    fn play_effect(&mut self, name: &'static str, volume: f64);
+    fn play_music(&mut self, name: &'static str, volume: f64);
+    fn set_music_volume(&mut self, volume: f64);
+}
+
+// plays the sounds of the registry with html audio elements
+pub struct HtmlBackend<'a> {
+    sounds: &'a Registry<HtmlAudioElement>,
+    music: Option<&'a HtmlAudioElement>,
+}
+
+impl<'a> HtmlBackend<'a> {
+    pub fn new(sounds: &'a Registry<HtmlAudioElement>) -> HtmlBackend<'a> {
+        HtmlBackend {
+            sounds,
+            music: None,
+        }
+    }
+}
+
+impl<'a> AudioBackend for HtmlBackend<'a> {
+    fn play_effect(&mut self, name: &'static str, volume: f64) {
+        // a copy of the element is played so that the same effect can overlap itself
+        let sound = match self
+            .sounds
+            .get(Category::Sound, name)
+            .clone_node()
+            .ok()
+            .and_then(|node| node.dyn_into::<HtmlAudioElement>().ok())
+        {
+            Some(sound) => sound,
+            None => return,
+        };
+        sound.set_volume(volume);
+        if sound.play().is_err() {
+            log!("failed to play {}", name);
+        }
+    }
+
+    fn play_music(&mut self, name: &'static str, volume: f64) {
+        if let Some(music) = self.music {
+            music.pause().ok();
+        }
+        let music = self.sounds.get(Category::Sound, name);
+        music.set_loop(true);
+        music.set_volume(volume);
+        // browsers refuse to play audio before the first interaction of the user
+        if music.play().is_err() {
+            log!("failed to play {}", name);
+        }
+        self.music = Some(music);
+    }
+
+    fn set_music_volume(&mut self, volume: f64) {
+        if let Some(music) = self.music {
+            music.set_volume(volume);
+        }
+    }
+}
+
+// chooses the sounds matching what happens in the game
+pub struct AudioManager<B: AudioBackend> {
+    backend: B,
+    master_volume: f64,
+    music_volume: f64,
+    effects_volume: f64,
+    music_started: bool,
+}
+
+impl<B: AudioBackend> AudioManager<B> {
+    pub fn new(backend: B) -> AudioManager<B> {
+        AudioManager {
+            backend,
+            master_volume: 1.0,
+            music_volume: 0.5,
+            effects_volume: 1.0,
+            music_started: false,
+        }
+    }
+
+    pub fn get_sound(event: &GameEvent) -> Option<&'static str> {
+        match event {
+            GameEvent::UnitMoved(_from, _unit_type, _to, _path) => Some("footsteps"),
+            GameEvent::UnitAttacked(_position, _unit_type, attack, _target, _target_type) => {
+                match attack {
+                    Attack::OffensiveSwordFight | Attack::DefensiveSwordFight => {
+                        Some("sword_clash")
+                    }
+                    Attack::VolleyOfArrows => Some("arrow_volley"),
+                    Attack::StickKnock | Attack::Heal => None,
+                }
+            }
+            GameEvent::UnitDied(_position, _unit_type) => Some("death"),
+            GameEvent::TurnStarted(_turn) => Some("turn_start"),
+            GameEvent::UnitDamaged(..) | GameEvent::UnitHealed(..) | GameEvent::UnitPushed(..) => {
+                None
+            }
+        }
+    }
+
+    fn get_music_volume(&self) -> f64 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn get_volume(&self, mixer: Mixer) -> f64 {
+        match mixer {
+            Mixer::Master => self.master_volume,
+            Mixer::Music => self.music_volume,
+            Mixer::Effects => self.effects_volume,
+        }
+    }
+
+    // value: from 0 to 1
+    pub fn set_volume(&mut self, mixer: Mixer, value: f64) {
+        let value = value.max(0.0).min(1.0);
+        match mixer {
+            Mixer::Master => self.master_volume = value,
+            Mixer::Music => self.music_volume = value,
+            Mixer::Effects => self.effects_volume = value,
+        }
+        self.backend.set_music_volume(self.get_music_volume());
+    }
+
+    // the music starts with the first action of the user because browsers block autoplay
+    pub fn handle_intent(&mut self, intent: &Intent) {
+        match intent {
+            Intent::HoverHex(_) | Intent::HoverNone => (),
+            _ if !self.music_started => {
+                self.music_started = true;
+                self.backend.play_music(MUSIC, self.get_music_volume());
+            }
+            _ => (),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        let volume = self.master_volume * self.effects_volume;
+        if volume <= 0.0 {
+            return;
+        }
+        if let Some(name) = Self::get_sound(event) {
+            self.backend.play_effect(name, volume);
+        }
+    }
+}
+
+// records what would have been played
+#[cfg(test)]
+#[derive(Default)]
+pub struct NullBackend {
+    pub effects: Vec<(&'static str, f64)>,
+    pub music: Option<(&'static str, f64)>,
+}
+
+#[cfg(test)]
+impl AudioBackend for NullBackend {
+    fn play_effect(&mut self, name: &'static str, volume: f64) {
+        self.effects.push((name, volume));
+    }
+
+    fn play_music(&mut self, name: &'static str, volume: f64) {
+        self.music = Some((name, volume));
+    }
+
+    fn set_music_volume(&mut self, volume: f64) {
+        if let Some((_name, music_volume)) = &mut self.music {
+            *music_volume = volume;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{idx::HexIndex, units::UnitType};
+    use std::convert::TryInto;
+
+    #[test]
+    fn sounds() {
+        let position: HexIndex = 3.try_into().unwrap();
+        let target: HexIndex = 4.try_into().unwrap();
+        let mut audio = AudioManager::new(NullBackend::default());
+
+        audio.handle_intent(&Intent::HoverNone);
+        assert_eq!(audio.backend.music, None);
+        audio.handle_intent(&Intent::EndTurn);
+        assert_eq!(audio.backend.music, Some(("ambient", 0.5)));
+
+        audio.handle_event(&GameEvent::UnitMoved(
+            position,
+            UnitType::Archer,
+            target,
+            vec![target],
+        ));
+        audio.handle_event(&GameEvent::UnitAttacked(
+            position,
+            UnitType::Archer,
+            Attack::VolleyOfArrows,
+            target,
+            Some(UnitType::Barbarian),
+        ));
+        audio.handle_event(&GameEvent::UnitDamaged(target, UnitType::Barbarian, 2));
+        audio.handle_event(&GameEvent::UnitDied(target, UnitType::Barbarian));
+        assert_eq!(
+            audio.backend.effects,
+            vec![("footsteps", 1.0), ("arrow_volley", 1.0), ("death", 1.0)]
+        );
+
+        audio.set_volume(Mixer::Master, 0.5);
+        audio.set_volume(Mixer::Effects, 0.5);
+        assert_eq!(audio.backend.music, Some(("ambient", 0.25)));
+        audio.handle_event(&GameEvent::TurnStarted(2));
+        assert_eq!(audio.backend.effects.last(), Some(&("turn_start", 0.25)));
+
+        audio.set_volume(Mixer::Effects, -1.0);
+        audio.handle_event(&GameEvent::TurnStarted(3));
+        assert_eq!(audio.backend.effects.len(), 4);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    assets::Category, input::*, layers::*, map::*, pause::Pause, results::Results, scene::*,
+    tooltip::*, touch::TouchInput, units::*, viewport::Viewport,
+};
+use std::{cell::RefCell, convert::TryInto};
+use wasm_game_lib::graphics::{canvas::*, font::Font};
+use wasm_game_lib::inputs::event::Event;
+
+pub struct Battle<'a> {
+    scenario: usize, // index in the scenarios of the context
+    map: Map<'a>,
+    units: Units<'a>,
+    tooltip: Tooltip<'a>,
+    input: InputHandler<'a>,
+    queued_intents: Vec<Intent>,
+    arial: &'a Font,
+    outcome: Option<Outcome>,
+    viewport: &'a RefCell<Viewport>,
+    layers: Compositor,
+}
+
+impl<'a> Battle<'a> {
+    pub fn new(context: &Context<'a>, scenario: usize) -> Battle<'a> {
+        let textures = context.textures;
+        let red = textures.get(Category::Overlay, "red");
+        let mut units = Units::new(textures, context.icons, context.viewport, context.arial);
+        for (index, unit_type) in &context.scenarios[scenario].units {
+            units.set(
+                &(*index).try_into().unwrap(),
+                Some(Unit::new(unit_type.clone())),
+            );
+        }
+
+        Battle {
+            scenario,
+            map: Map::new(textures, context.viewport, &context.scenarios[scenario]),
+            units,
+            tooltip: Tooltip::new(red, context.viewport),
+            input: InputHandler::new(context.viewport, context.preferences),
+            queued_intents: Vec::new(),
+            arial: context.arial,
+            outcome: None,
+            viewport: context.viewport,
+            layers: Compositor::default(),
+        }
+    }
+
+    // most pointer events change nothing drawn, such as a move inside a button
+    fn invalidate_pointer(&mut self) {
+        let cursor_moved = self.input.poll_cursor_moved();
+        if self.units.poll_margin_changes() || cursor_moved {
+            self.layers.invalidate(Input::Pointer);
+        }
+    }
+}
+
+impl<'a> Scene<'a> for Battle<'a> {
+    fn handle_event(&mut self, event: Event) {
+        self.input.handle_event(event, &mut self.units);
+        self.invalidate_pointer();
+    }
+
+    fn handle_touch(&mut self, touch: TouchInput) {
+        self.input.handle_touch(touch, &mut self.units);
+        self.invalidate_pointer();
+    }
+
+    fn update(&mut self, delta: f64, context: &Context<'a>) -> Transition<'a> {
+        let mut transition = Transition::None;
+        self.input.update(delta);
+
+        // intents are delayed while the consequences of the last action are animated
+        self.queued_intents.append(&mut self.input.poll_intents());
+        for intent in self.queued_intents.split_off(0) {
+            if !intent.is_immediate()
+                && (self.units.is_animating() || !self.queued_intents.is_empty())
+            {
+                self.queued_intents.push(intent);
+            } else {
+                // even hovering a hex can previsualise the lives lost by an attack
+                self.layers.invalidate(Input::Selection);
+                self.layers.invalidate(Input::Board);
+                self.layers.invalidate(Input::Texts);
+                if intent == Intent::Pause {
+                    transition = Transition::Push(Box::new(Pause::new(context)));
+                }
+                context.audio.borrow_mut().handle_intent(&intent);
+                self.tooltip.handle_intent(&intent);
+                self.units.handle_intent(&intent, &mut self.map, self.arial);
+            }
+        }
+
+        for event in self.units.poll_events() {
+            self.units.combat_log.handle_event(&event);
+            self.units.animations.handle_event(&event);
+            context.audio.borrow_mut().handle_event(&event);
+            self.layers.invalidate(Input::Board);
+            self.layers.invalidate(Input::Texts);
+        }
+
+        let animation_speed = context.preferences.borrow().animation_speed;
+        self.units.update(delta * animation_speed);
+        if self.units.is_animating() {
+            self.layers.invalidate(Input::Animation);
+        }
+        if self.units.is_blinking() {
+            self.layers.invalidate(Input::Blink);
+        }
+        if self.tooltip.update(delta, &self.units, &self.map) {
+            self.layers.invalidate(Input::Texts);
+        }
+
+        // the results are shown once the last blow has been animated
+        if self.outcome.is_none() && !self.units.is_animating() {
+            self.outcome = self.units.get_outcome();
+            if let Some(outcome) = self.outcome {
+                let turns = self.units.get_turn();
+                let results = Results::new(context, outcome, turns, self.scenario);
+                transition = Transition::Push(Box::new(results));
+            }
+        }
+
+        transition
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas) {
+        self.units.layout_margin(canvas);
+
+        // only the layers whose inputs changed are drawn again
+        self.layers.set_viewport(*self.viewport.borrow());
+        if let Some(layer) = self.layers.redraw(Layer::Terrain) {
+            layer.draw(&self.map);
+        }
+        if let Some(layer) = self.layers.redraw(Layer::Highlights) {
+            self.units.draw_highlights(layer);
+            layer.draw(&self.input);
+        }
+        if let Some(layer) = self.layers.redraw(Layer::Units) {
+            self.units.draw_units(layer);
+        }
+        if let Some(layer) = self.layers.redraw(Layer::Telegraphs) {
+            self.units.draw_telegraphs(layer);
+        }
+        if let Some(layer) = self.layers.redraw(Layer::Ui) {
+            self.units.draw_margin(layer);
+            layer.draw(&self.tooltip);
+        }
+        canvas.draw(&self.layers);
+    }
+
+    // a finished battle cannot be continued
+    fn is_resumable(&self) -> bool {
+        self.outcome.is_none()
+    }
+
+    fn translate(&mut self, _context: &Context<'a>) {
+        self.units.translate(self.arial);
+        self.layers.invalidate(Input::Texts);
+    }
+}
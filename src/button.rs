@@ -1,3 +1,4 @@
+use crate::{layout::Rect, widget::*};
 use std::cell::RefCell;
 use wasm_bindgen::JsValue;
 use wasm_game_lib::graphics::canvas::*;
@@ -5,7 +6,6 @@ use wasm_game_lib::graphics::color::*;
 use wasm_game_lib::graphics::drawable::*;
 use wasm_game_lib::graphics::font::*;
 use wasm_game_lib::graphics::text::*;
-use wasm_game_lib::inputs::mouse::{get_mouse_position, is_mouse_pressed};
 
 const BUTTON_STYLE: LineStyle = LineStyle {
     cap: LineCap::Round,
@@ -19,10 +19,13 @@ const BUTTON_STYLE: LineStyle = LineStyle {
     },
 };
 
+pub const BUTTON_HEIGHT: f64 = 50.0;
+
 pub struct Button<'a> {
     pub coords: (f64, f64),
     pub width: RefCell<Option<f64>>,
     pub text: Text<'a>,
+    state: WidgetState,
 }
 
 impl<'a> Button<'a> {
@@ -39,17 +42,34 @@ impl<'a> Button<'a> {
             coords,
             width: RefCell::new(width),
             text,
+            state: WidgetState::default(),
         }
     }
 
-    pub fn is_hover_with_mouse_position(&self, mouse_position: (u32, u32)) -> bool {
-        let mouse_position = (mouse_position.0 as f64, mouse_position.1 as f64);
-        let width = self.width.borrow().unwrap();
+    pub fn set_coords(&mut self, coords: (f64, f64)) {
+        self.coords = coords;
+        self.text.coords = (coords.0 as usize + 7, coords.1 as usize + 35);
+    }
+}
+
+impl<'a> Widget for Button<'a> {
+    // the width is unknown until the text is measured by the first draw
+    fn get_area(&self) -> Rect {
+        let width = self.width.borrow().unwrap_or(0.0);
+        (self.coords.0, self.coords.1, width, BUTTON_HEIGHT)
+    }
 
-        mouse_position.0 > self.coords.0
-            && mouse_position.0 < self.coords.0 + width
-            && mouse_position.1 > self.coords.1
-            && mouse_position.1 < self.coords.1 + 50.0
+    fn set_area(&mut self, area: Rect) {
+        self.set_coords((area.0, area.1));
+        *self.width.borrow_mut() = Some(area.2);
+    }
+
+    fn get_state(&self) -> WidgetState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: WidgetState) {
+        self.state = state;
     }
 }
 
@@ -64,25 +84,16 @@ impl<'a> Drawable for Button<'a> {
         let context = canvas.get_2d_canvas_rendering_context();
 
         context.begin_path();
-        context.stroke_rect(self.coords.0, self.coords.1, width, 50.0);
-        let mouse_position = get_mouse_position();
-        let mouse_position = (mouse_position.0 as f64, mouse_position.1 as f64);
-
-        if mouse_position.0 > self.coords.0
-            && mouse_position.0 < self.coords.0 + width
-            && mouse_position.1 > self.coords.1
-            && mouse_position.1 < self.coords.1 + 50.0
-        {
-            if is_mouse_pressed() {
-                context.set_fill_style(&JsValue::from_str("rgb(44, 48, 79)"));
-            } else {
-                context.set_fill_style(&JsValue::from_str("rgb(34, 38, 59)"));
-            }
+        context.stroke_rect(self.coords.0, self.coords.1, width, BUTTON_HEIGHT);
+        if self.state.pressed {
+            context.set_fill_style(&JsValue::from_str("rgb(44, 48, 79)"));
+        } else if self.state.hovered {
+            context.set_fill_style(&JsValue::from_str("rgb(34, 38, 59)"));
         } else {
             context.set_fill_style(&JsValue::from_str("rgb(24, 28, 39)"));
         }
 
-        context.fill_rect(self.coords.0, self.coords.1, width, 50.0);
+        context.fill_rect(self.coords.0, self.coords.1, width, BUTTON_HEIGHT);
         context.stroke();
         canvas.draw(&self.text);
     }
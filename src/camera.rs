@@ -0,0 +1,171 @@
+pub const MIN_ZOOM: f64 = 1.0;
+pub const MAX_ZOOM: f64 = 3.0;
+const ZOOM_STEP: f64 = 1.1;
+const DRAG_THRESHOLD: f64 = 5.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub pan: (f64, f64), // in screen pixels, relative to the centered board
+    pub zoom: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera {
+            pan: (0.0, 0.0),
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    // origin: screen position of the board without camera, factor: scale of the board at zoom 1
+    // the board point under the cursor stays at the same place on the screen
+    pub fn zoom_at(&mut self, cursor: (f64, f64), origin: (f64, f64), factor: f64, ratio: f64) {
+        let new_zoom = (self.zoom * ratio).max(MIN_ZOOM).min(MAX_ZOOM);
+        let board_x = (cursor.0 - origin.0 - self.pan.0) / (factor * self.zoom);
+        let board_y = (cursor.1 - origin.1 - self.pan.1) / (factor * self.zoom);
+
+        self.zoom = new_zoom;
+        self.pan.0 = cursor.0 - origin.0 - board_x * factor * new_zoom;
+        self.pan.1 = cursor.1 - origin.1 - board_y * factor * new_zoom;
+    }
+
+    pub fn zoom_in_at(&mut self, cursor: (f64, f64), origin: (f64, f64), factor: f64) {
+        self.zoom_at(cursor, origin, factor, ZOOM_STEP)
+    }
+
+    pub fn zoom_out_at(&mut self, cursor: (f64, f64), origin: (f64, f64), factor: f64) {
+        self.zoom_at(cursor, origin, factor, 1.0 / ZOOM_STEP)
+    }
+
+    // point: internal canvas coordinates
+    pub fn center_on(
+        &mut self,
+        point: (f64, f64),
+        origin: (f64, f64),
+        factor: f64,
+        area_center: (f64, f64),
+    ) {
+        self.pan.0 = area_center.0 - origin.0 - point.0 * factor * self.zoom;
+        self.pan.1 = area_center.1 - origin.1 - point.1 * factor * self.zoom;
+    }
+
+    // area: (x, y, width, height) of the screen area where the board is displayed
+    // board_size: size of the board on the screen at zoom 1
+    pub fn clamp(
+        &mut self,
+        origin: (f64, f64),
+        board_size: (f64, f64),
+        area: (f64, f64, f64, f64),
+    ) {
+        self.pan.0 = clamp_axis(
+            self.pan.0,
+            origin.0,
+            board_size.0 * self.zoom,
+            area.0,
+            area.2,
+        );
+        self.pan.1 = clamp_axis(
+            self.pan.1,
+            origin.1,
+            board_size.1 * self.zoom,
+            area.1,
+            area.3,
+        );
+    }
+}
+
+fn clamp_axis(pan: f64, origin: f64, board_size: f64, area_start: f64, area_size: f64) -> f64 {
+    let (min, max) = if board_size >= area_size {
+        // the board must cover the whole area
+        (
+            area_start + area_size - board_size - origin,
+            area_start - origin,
+        )
+    } else {
+        // the board must be entirely visible
+        (
+            area_start - origin,
+            area_start + area_size - board_size - origin,
+        )
+    };
+    pan.max(min).min(max)
+}
+
+#[derive(Default)]
+pub struct Drag {
+    last_position: Option<(u32, u32)>,
+    distance: f64,
+}
+
+impl Drag {
+    // returns the movement of the mouse since the last call while the mouse is pressed
+    pub fn handle_mouse_move(&mut self, x: u32, y: u32, pressed: bool) -> Option<(f64, f64)> {
+        if !pressed {
+            self.last_position = None;
+            return None;
+        }
+
+        let movement = self
+            .last_position
+            .map(|(last_x, last_y)| (x as f64 - last_x as f64, y as f64 - last_y as f64));
+        if self.last_position.is_none() {
+            self.distance = 0.0;
+        }
+        self.last_position = Some((x, y));
+
+        if let Some(movement) = movement {
+            self.distance += (movement.0 * movement.0 + movement.1 * movement.1).sqrt();
+        }
+        movement
+    }
+
+    // a click ending a drag must not be handled as a click
+    pub fn is_drag(&mut self) -> bool {
+        let is_drag = self.distance > DRAG_THRESHOLD;
+        self.distance = 0.0;
+        is_drag
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn zooming_keeps_cursor_point() {
+        let mut camera = Camera::default();
+        let origin = (100.0, 0.0);
+        let factor = 0.5;
+        let cursor = (300.0, 200.0);
+        let board_point = (
+            (cursor.0 - origin.0 - camera.pan.0) / (factor * camera.zoom),
+            (cursor.1 - origin.1 - camera.pan.1) / (factor * camera.zoom),
+        );
+
+        camera.zoom_in_at(cursor, origin, factor);
+        assert!(camera.zoom > 1.0);
+        let new_board_point = (
+            (cursor.0 - origin.0 - camera.pan.0) / (factor * camera.zoom),
+            (cursor.1 - origin.1 - camera.pan.1) / (factor * camera.zoom),
+        );
+        assert!((board_point.0 - new_board_point.0).abs() < 0.001);
+        assert!((board_point.1 - new_board_point.1).abs() < 0.001);
+
+        for _ in 0..100 {
+            camera.zoom_out_at(cursor, origin, factor);
+        }
+        assert!((camera.zoom - MIN_ZOOM).abs() < 0.001);
+    }
+
+    #[test]
+    fn clamping() {
+        let mut camera = Camera {
+            pan: (-1000.0, 1000.0),
+            zoom: 1.0,
+        };
+        camera.clamp((100.0, 0.0), (500.0, 400.0), (100.0, 0.0, 500.0, 500.0));
+        assert_eq!(camera.pan, (0.0, 100.0));
+    }
+}
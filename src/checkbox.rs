@@ -0,0 +1,111 @@
+use crate::{layout::Rect, widget::*};
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::canvas::*;
+use wasm_game_lib::graphics::color::*;
+use wasm_game_lib::graphics::drawable::*;
+use wasm_game_lib::graphics::font::*;
+use wasm_game_lib::graphics::text::*;
+
+const BOX_STYLE: LineStyle = LineStyle {
+    cap: LineCap::Round,
+    join: LineJoin::Round,
+    size: 4.0,
+    color: Color {
+        alpha: 255,
+        red: 67,
+        green: 75,
+        blue: 96,
+    },
+};
+
+const BOX_SIZE: f64 = 30.0;
+const FONT_SIZE: usize = 20;
+
+// a box followed by its label, the label can be clicked too
+pub struct Checkbox<'a> {
+    area: Rect,
+    label: Text<'a>,
+    pub checked: bool,
+    state: WidgetState,
+}
+
+impl<'a> Checkbox<'a> {
+    pub fn new(coords: (f64, f64), width: f64, font: &'a Font, label: String) -> Checkbox<'a> {
+        let mut label = Text::new_with_text_and_coords(&font, label, (0, 0));
+        label.style.color = Color::white();
+        label.character_size = (FONT_SIZE, "px");
+
+        let mut checkbox = Checkbox {
+            area: (0.0, 0.0, 0.0, 0.0),
+            label,
+            checked: false,
+            state: WidgetState::default(),
+        };
+        checkbox.set_area((coords.0, coords.1, width, BOX_SIZE));
+        checkbox
+    }
+}
+
+impl<'a> Widget for Checkbox<'a> {
+    fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    // the height is always the size of the box
+    fn set_area(&mut self, area: Rect) {
+        self.area = (area.0, area.1, area.2, BOX_SIZE);
+        self.label.coords = (
+            (area.0 + BOX_SIZE * 1.5) as usize,
+            (area.1 + BOX_SIZE / 2.0) as usize + FONT_SIZE / 3,
+        );
+    }
+
+    fn get_state(&self) -> WidgetState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: WidgetState) {
+        self.state = state;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> Response {
+        match update_state(self, event) {
+            Response::Clicked => {
+                self.checked = !self.checked;
+                Response::Changed
+            }
+            response => response,
+        }
+    }
+}
+
+impl<'a> Drawable for Checkbox<'a> {
+    fn draw_on_canvas(&self, mut canvas: &mut Canvas) {
+        let (x, y, _width, _height) = self.area;
+
+        BOX_STYLE.apply_on_canvas(&mut canvas);
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.begin_path();
+        context.stroke_rect(x, y, BOX_SIZE, BOX_SIZE);
+        if self.state.pressed {
+            context.set_fill_style(&JsValue::from_str("rgb(54, 58, 99)"));
+        } else if self.state.hovered {
+            context.set_fill_style(&JsValue::from_str("rgb(44, 48, 79)"));
+        } else {
+            context.set_fill_style(&JsValue::from_str("rgb(24, 28, 39)"));
+        }
+        context.fill_rect(x, y, BOX_SIZE, BOX_SIZE);
+        context.stroke();
+
+        if self.checked {
+            context.set_stroke_style(&JsValue::from_str("white"));
+            context.begin_path();
+            context.move_to(x + BOX_SIZE * 0.2, y + BOX_SIZE * 0.5);
+            context.line_to(x + BOX_SIZE * 0.4, y + BOX_SIZE * 0.75);
+            context.line_to(x + BOX_SIZE * 0.8, y + BOX_SIZE * 0.25);
+            context.stroke();
+        }
+
+        canvas.draw(&self.label);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::{
+    events::*, i18n::*, idx::HexIndex, layout::Rect, scroll_panel::ScrollPanel, units::*,
+    viewport::*, widget::*,
+};
+use std::cell::RefCell;
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::{canvas::*, color::*, drawable::*, image::*};
+
+const BOX_STYLE: LineStyle = LineStyle {
+    cap: LineCap::Round,
+    join: LineJoin::Round,
+    size: 4.0,
+    color: Color {
+        alpha: 255,
+        red: 67,
+        green: 75,
+        blue: 96,
+    },
+};
+
+const MARGIN: f64 = 10.0;
+const FONT_SIZE: f64 = 16.0;
+const LINE_HEIGHT: f64 = 20.0;
+const MAX_ENTRIES: usize = 200;
+
+pub struct LogEntry {
+    pub message: String,
+    pub hexes: Vec<HexIndex>,
+    details: usize,     // number of consequences appended to the message
+    lines: Vec<String>, // the wrapped message, empty until the next layout
+}
+
+pub struct CombatLog<'a> {
+    entries: Vec<LogEntry>,
+    panel: ScrollPanel,
+    pointer: Option<(f64, f64)>, // position of the pointer hovering the log
+    viewport: &'a RefCell<Viewport>,
+    highlight: &'a Image,
+    last_target: Option<HexIndex>,
+}
+
+impl<'a> CombatLog<'a> {
+    pub fn new(highlight: &'a Image, viewport: &'a RefCell<Viewport>) -> CombatLog<'a> {
+        CombatLog {
+            entries: Vec::new(),
+            panel: ScrollPanel::new((0.0, 0.0, 0.0, 0.0)),
+            pointer: None,
+            viewport,
+            highlight,
+            last_target: None,
+        }
+    }
+
+    fn push_entry(&mut self, message: String, hexes: Vec<HexIndex>) {
+        self.entries.push(LogEntry {
+            message,
+            hexes,
+            details: 0,
+            lines: Vec::new(),
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            // keep the displayed entries in place when the player scrolled up
+            let removed = self.entries.remove(0);
+            self.panel
+                .scroll_by(-(removed.lines.len() as f64) * LINE_HEIGHT);
+        }
+        self.last_target = None;
+    }
+
+    fn push_detail(&mut self, position: HexIndex, unit_type: &UnitType, detail: String) {
+        let target = self.last_target;
+        let entry = match (target, self.entries.last_mut()) {
+            (Some(_), Some(entry)) => entry,
+            _ => return,
+        };
+
+        entry.message.push_str(if entry.details == 0 {
+            tr("log.first_separator")
+        } else {
+            tr("log.separator")
+        });
+        if Some(position) != target {
+            let name = unit_type.get_name();
+            entry
+                .message
+                .push_str(&tr_args("log.detail", &[&detail, &name]));
+        } else {
+            entry.message.push_str(&detail);
+        }
+        entry.details += 1;
+        entry.lines.clear();
+
+        if !entry.hexes.contains(&position) {
+            entry.hexes.push(position);
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::TurnStarted(turn) => {
+                self.push_entry(tr_args("log.turn", &[turn]), Vec::new());
+            }
+            GameEvent::UnitMoved(from, unit_type, _to, path) => {
+                let name = unit_type.get_name();
+                let mut hexes = vec![*from];
+                hexes.extend_from_slice(path);
+                self.push_entry(tr_args("log.moved", &[&name, &path.len()]), hexes);
+            }
+            GameEvent::UnitAttacked(position, unit_type, attack, target, target_type) => {
+                let message = match target_type {
+                    Some(target_type) if position == target => {
+                        tr_args("log.used", &[&target_type.get_name(), &attack.get_name()])
+                    }
+                    Some(target_type) => tr_args(
+                        "log.used_on",
+                        &[
+                            &unit_type.get_name(),
+                            &attack.get_name(),
+                            &target_type.get_name(),
+                        ],
+                    ),
+                    None => tr_args("log.used", &[&unit_type.get_name(), &attack.get_name()]),
+                };
+                self.push_entry(message, vec![*position, *target]);
+                self.last_target = Some(*target);
+            }
+            GameEvent::UnitDamaged(position, unit_type, damage) => {
+                self.push_detail(*position, unit_type, tr_args("log.damage", &[damage]));
+            }
+            GameEvent::UnitHealed(position, unit_type, amount) => {
+                self.push_detail(*position, unit_type, tr_args("log.healed", &[amount]));
+            }
+            GameEvent::UnitPushed(from, unit_type, to, direction) => {
+                let detail = tr_args("log.pushed", &[&direction.get_name()]);
+                self.push_detail(*from, unit_type, detail);
+                if self.last_target == Some(*from) {
+                    self.last_target = Some(*to);
+                }
+                if let Some(entry) = self.entries.last_mut() {
+                    if !entry.hexes.contains(to) {
+                        entry.hexes.push(*to);
+                    }
+                }
+            }
+            GameEvent::UnitDied(position, unit_type) => {
+                if self.last_target.is_some() {
+                    self.push_detail(*position, unit_type, tr("log.killed").to_string());
+                } else {
+                    self.push_entry(
+                        tr_args("log.died", &[&unit_type.get_name()]),
+                        vec![*position],
+                    );
+                }
+            }
+        }
+    }
+
+    // wraps the new entries and keeps the newest ones in view unless the player scrolled up
+    pub fn layout(&mut self, canvas: &mut Canvas, area: Rect) {
+        let width = area.2 - MARGIN * 2.0;
+        let rewrap = width != self.panel.get_area().2 - MARGIN * 2.0;
+        let following = self.panel.is_scrolled_to_end();
+        self.panel.set_area(area);
+
+        canvas
+            .get_2d_canvas_rendering_context()
+            .set_font(&format!("{}px Arial", FONT_SIZE));
+        for entry in self.entries.iter_mut() {
+            if rewrap || entry.lines.is_empty() {
+                entry.lines = CombatLog::wrap(canvas, &entry.message, width);
+            }
+        }
+
+        let lines: usize = self.entries.iter().map(|entry| entry.lines.len()).sum();
+        self.panel
+            .set_content_height(lines as f64 * LINE_HEIGHT + MARGIN * 2.0);
+        if following {
+            self.panel.scroll_to_end();
+        }
+    }
+
+    // index of the entry under the pointer, its hexes are highlighted on the board
+    fn get_hovered_entry(&self) -> Option<usize> {
+        let pointer = self.pointer?;
+        let mut top = self.panel.get_area().1 + MARGIN - self.panel.get_offset();
+        for (idx, entry) in self.entries.iter().enumerate() {
+            let bottom = top + entry.lines.len() as f64 * LINE_HEIGHT;
+            if pointer.1 > top && pointer.1 < bottom {
+                return Some(idx);
+            }
+            top = bottom;
+        }
+        None
+    }
+
+    fn wrap(canvas: &mut Canvas, message: &str, max_width: f64) -> Vec<String> {
+        let context = canvas.get_2d_canvas_rendering_context();
+        let mut lines = Vec::new();
+        let mut line = String::new();
+
+        for word in message.split(' ') {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            let width = context
+                .measure_text(&candidate)
+                .map(|m| m.width())
+                .unwrap_or(0.0);
+            if width > max_width && !line.is_empty() {
+                lines.push(line);
+                line = word.to_string();
+            } else {
+                line = candidate;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    fn draw_highlight(&self, canvas: &mut Canvas, hexes: &[HexIndex]) {
+        let viewport = *self.viewport.borrow();
+        let factor = viewport.get_factor();
+        let area = viewport.get_board_area();
+
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.save();
+        context.begin_path();
+        context.rect(area.0, area.1, area.2, area.3);
+        context.clip();
+
+        for hex in hexes {
+            let (x, y) = viewport.hex_to_screen(&hex);
+
+            canvas
+                .get_2d_canvas_rendering_context()
+                .draw_image_with_html_image_element_and_dw_and_dh(
+                    self.highlight.get_html_element(),
+                    x as f64,
+                    y as f64,
+                    256.0 * factor,
+                    384.0 * factor,
+                )
+                .unwrap();
+        }
+
+        canvas.get_2d_canvas_rendering_context().restore();
+    }
+}
+
+impl<'a> Widget for CombatLog<'a> {
+    fn get_area(&self) -> Rect {
+        self.panel.get_area()
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.panel.set_area(area);
+    }
+
+    fn get_state(&self) -> WidgetState {
+        self.panel.get_state()
+    }
+
+    fn set_state(&mut self, state: WidgetState) {
+        self.panel.set_state(state);
+    }
+
+    // the log cannot be clicked but it hides the board below it
+    fn handle_event(&mut self, event: &UiEvent) -> Response {
+        let hovered_entry = self.get_hovered_entry();
+        self.pointer = match *event {
+            UiEvent::PointerMove(position, _) if self.hit_test(position, 0.0) => Some(position),
+            UiEvent::PointerMove(..) | UiEvent::PointerLeave => None,
+            _ => self.pointer,
+        };
+        let response = match self.panel.handle_event(event) {
+            Response::Clicked => Response::Consumed,
+            response => response,
+        };
+        if response == Response::Consumed && self.get_hovered_entry() != hovered_entry {
+            Response::Changed
+        } else {
+            response
+        }
+    }
+}
+
+impl<'a> Drawable for CombatLog<'a> {
+    fn draw_on_canvas(&self, mut canvas: &mut Canvas) {
+        let (x, y, width, height) = self.panel.get_area();
+        if height < LINE_HEIGHT + MARGIN * 2.0 {
+            return;
+        }
+
+        BOX_STYLE.apply_on_canvas(&mut canvas);
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.begin_path();
+        context.stroke_rect(x, y, width, height);
+        context.set_fill_style(&JsValue::from_str("rgb(24, 28, 39)"));
+        context.fill_rect(x, y, width, height);
+        context.stroke();
+        context.set_font(&format!("{}px Arial", FONT_SIZE));
+
+        let hovered_entry = self.get_hovered_entry();
+        self.panel.draw_content(canvas, |canvas, origin| {
+            // the newest entries are at the bottom of the content
+            let context = canvas.get_2d_canvas_rendering_context();
+            let mut top = origin.1 + MARGIN;
+            for (entry_idx, entry) in self.entries.iter().enumerate() {
+                let bottom = top + entry.lines.len() as f64 * LINE_HEIGHT;
+                if bottom < y || top > y + height {
+                    // hidden by the panel
+                    top = bottom;
+                    continue;
+                }
+                if hovered_entry == Some(entry_idx) {
+                    context.set_fill_style(&JsValue::from_str("rgb(44, 48, 79)"));
+                    context.fill_rect(x + MARGIN / 2.0, top, width - MARGIN, bottom - top);
+                }
+
+                context.set_fill_style(&JsValue::from_str("white"));
+                for (idx, line) in entry.lines.iter().enumerate() {
+                    context
+                        .fill_text(
+                            line,
+                            origin.0 + MARGIN,
+                            top + (idx + 1) as f64 * LINE_HEIGHT - (LINE_HEIGHT - FONT_SIZE),
+                        )
+                        .unwrap();
+                }
+                top = bottom;
+            }
+        });
+
+        if let Some(entry_idx) = hovered_entry {
+            self.draw_highlight(&mut canvas, &self.entries[entry_idx].hexes);
+        }
+    }
+}
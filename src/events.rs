@@ -0,0 +1,14 @@
+use crate::{actions::Attack, idx::HexIndex, map::Direction, units::UnitType};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    UnitMoved(HexIndex, UnitType, HexIndex, Vec<HexIndex>), // from, unit, to, path
+    // attacker, type of the attacker, attack, target, type of the unit on the target if any
+    UnitAttacked(HexIndex, UnitType, Attack, HexIndex, Option<UnitType>),
+    // the type of the unit is kept since it may have left the hex when the event is read
+    UnitDamaged(HexIndex, UnitType, usize), // position, unit, damage
+    UnitHealed(HexIndex, UnitType, usize),  // position, unit, restored life points
+    UnitPushed(HexIndex, UnitType, HexIndex, Direction), // from, unit, to, direction
+    UnitDied(HexIndex, UnitType),           // position, unit
+    TurnStarted(usize),
+}
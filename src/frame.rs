@@ -0,0 +1,81 @@
+use js_sys::Promise;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+
+pub const FIXED_STEP: f64 = 1000.0 / 60.0; // in milliseconds
+const MAX_STEPS: usize = 10; // the time above it is dropped instead of being caught up
+
+// the game is updated in fixed steps whatever the frame rate of the screen
+#[derive(Default)]
+pub struct FrameClock {
+    last_frame: Option<f64>,
+    accumulator: f64, // time not simulated yet
+}
+
+impl FrameClock {
+    // returns the number of steps to run for the frame drawn at now (in milliseconds)
+    pub fn advance(&mut self, now: f64) -> usize {
+        let delta = match self.last_frame {
+            Some(last_frame) => (now - last_frame).max(0.0),
+            None => 0.0,
+        };
+        self.last_frame = Some(now);
+        self.accumulator = (self.accumulator + delta).min(FIXED_STEP * MAX_STEPS as f64);
+        let steps = (self.accumulator / FIXED_STEP) as usize;
+        self.accumulator -= FIXED_STEP * steps as f64;
+        steps
+    }
+
+    // the time spent paused is not simulated
+    pub fn pause(&mut self) {
+        self.last_frame = None;
+        self.accumulator = 0.0;
+    }
+}
+
+// resolves with the timestamp of the next frame, the browser does not run frames in hidden tabs
+pub async fn next_frame() -> f64 {
+    let promise = Promise::new(&mut |resolve, _reject| {
+        let requested = web_sys::window()
+            .map(|window| window.request_animation_frame(&resolve).is_ok())
+            .unwrap_or(false);
+        if !requested {
+            let now = JsValue::from_f64(js_sys::Date::now());
+            let _ = resolve.call1(&JsValue::NULL, &now);
+        }
+    });
+    match JsFuture::from(promise).await {
+        Ok(timestamp) => timestamp.as_f64().unwrap_or_else(js_sys::Date::now),
+        Err(_) => js_sys::Date::now(),
+    }
+}
+
+pub fn is_hidden() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .map(|document| document.hidden())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixed_steps() {
+        let mut clock = FrameClock::default();
+        assert_eq!(clock.advance(1000.0), 0);
+        // a 30 fps screen runs two steps per frame and a 120 fps one a step every other frame
+        assert_eq!(clock.advance(1034.0), 2);
+        let steps: Vec<usize> = (1..=4)
+            .map(|frame| clock.advance(1034.0 + frame as f64 * 1000.0 / 120.0))
+            .collect();
+        assert_eq!(steps, vec![0, 1, 0, 1]);
+
+        // a long freeze is not caught up
+        assert_eq!(clock.advance(60_000.0), MAX_STEPS);
+        clock.pause();
+        assert_eq!(clock.advance(120_000.0), 0);
+        assert_eq!(clock.advance(120_000.0 + FIXED_STEP + 1.0), 1);
+    }
+}
@@ -0,0 +1,351 @@
+use std::{cell::RefCell, fmt::Display};
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+pub struct Catalog {
+    pub locale: &'static str,
+    messages: &'static [(&'static str, &'static str)],
+}
+
+impl Catalog {
+    pub fn get(&self, key: &str) -> Option<&'static str> {
+        self.messages
+            .iter()
+            .find(|(k, _message)| *k == key)
+            .map(|(_key, message)| *message)
+    }
+}
+
+// arguments are written {0}, {1}...
+const EN: &[(&str, &str)] = &[
+    ("loading.message", "Loading ressources... Please wait"),
+    ("loading.failed", "Failed to load required ressources:"),
+    ("loading.replaced", "Some ressources are missing and have been replaced:"),
+    ("loading.placeholder", "{0} (placeholder)"),
+    ("loading.missing", "{0} (missing)"),
+    ("button.next_turn", "Next turn"),
+    ("button.pause", "Pause"),
+    ("menu.title", "Main menu"),
+    ("menu.continue", "Continue"),
+    ("menu.new_game", "New game"),
+    ("menu.scenarios", "Scenarios"),
+    ("menu.settings", "Settings"),
+    ("menu.back", "Back"),
+    ("menu.main_menu", "Main menu"),
+    ("pause.title", "Pause"),
+    ("pause.resume", "Resume"),
+    ("settings.title", "Settings"),
+    ("settings.master_volume", "Volume"),
+    ("settings.music_volume", "Music"),
+    ("settings.effects_volume", "Sound effects"),
+    ("settings.muted", "Mute"),
+    ("settings.language", "Language: {0}"),
+    ("settings.scroll_speed", "Scroll speed"),
+    ("settings.margin_ratio", "Side panel width"),
+    ("settings.animation_speed", "Animation speed"),
+    ("settings.controls", "Controls"),
+    ("settings.press_key", "Press a key"),
+    ("settings.unbound", "-"),
+    ("command.next_unit", "Next unit"),
+    ("command.previous_unit", "Previous unit"),
+    ("command.first_attack", "First attack"),
+    ("command.second_attack", "Second attack"),
+    ("command.next_turn", "Next turn"),
+    ("command.deselect", "Deselect"),
+    ("command.center_camera", "Center camera"),
+    ("command.cursor_top_left", "Cursor top left"),
+    ("command.cursor_top_right", "Cursor top right"),
+    ("command.cursor_right", "Cursor right"),
+    ("command.cursor_bottom_right", "Cursor bottom right"),
+    ("command.cursor_bottom_left", "Cursor bottom left"),
+    ("command.cursor_left", "Cursor left"),
+    ("command.activate_cursor", "Activate cursor"),
+    ("command.pause", "Pause"),
+    ("language.name", "English"),
+    ("scenario.first_blood", "First blood"),
+    ("scenario.ambush", "Ambush"),
+    ("results.victory", "Victory"),
+    ("results.defeat", "Defeat"),
+    ("results.turns", "The battle lasted {0} turns"),
+    ("results.play_again", "Play again"),
+    ("attack.stick_knock", "Stick Knock"),
+    ("attack.stick_knock.description", "Hit an adjacent unit (1 damage) and push it away."),
+    ("attack.volley_of_arrows", "Volley of Arrows"),
+    ("attack.volley_of_arrows.description", "Shoot arrows in one direction. The first ennemy on that direction will be damaged (1 damage) and pushed away."),
+    ("attack.offensive_sword_fight", "Offensive Sword Fight"),
+    ("attack.offensive_sword_fight.description", "Attack adjacent unit using sword (2 damage) and pull it (1 damage for both units)."),
+    ("attack.defensive_sword_fight", "Defensive Sword Fight"),
+    ("attack.defensive_sword_fight.description", "Attack adjacent unit using sword (2 damage) and push it away."),
+    ("attack.heal", "Heal"),
+    ("attack.heal.description", "Restore 1 LP. The healed unit will be restored at least to the third of the max LPs."),
+    ("unit.archer", "Archer"),
+    ("unit.knight", "Knight"),
+    ("unit.scout", "Scout"),
+    ("unit.barbarian", "Barbarian"),
+    ("unit.armored_barbarian", "Armored Barbarian"),
+    ("unit.barbarian_lord_of_death", "Barbarian Lord of Death"),
+    ("tile.grassy_plain", "Grassy plain"),
+    ("tile.forest", "Forest"),
+    ("tile.plain", "Plain"),
+    ("tile.rocks", "Rocks"),
+    ("tile.rule.cost", "Crossing it costs 1 move."),
+    ("tile.rule.hazard", "Units pushed into it take damage."),
+    ("direction.top_left", "Top Left"),
+    ("direction.top_right", "Top Right"),
+    ("direction.right", "Right"),
+    ("direction.bottom_right", "Bottom Right"),
+    ("direction.bottom_left", "Bottom Left"),
+    ("direction.left", "Left"),
+    ("panel.life", "Life: {0}/{1}"),
+    ("panel.moves", "Moves: {0}/{1}"),
+    ("panel.action_available", "Action available"),
+    ("panel.action_used", "Action used"),
+    ("panel.will_use", "Will use {0}"),
+    ("panel.toward", "{0} toward {1}"),
+    ("panel.then", " then "),
+    ("panel.order", "{0} (acts #{1})"),
+    ("log.turn", "Turn {0}"),
+    ("log.moved", "{0} moved {1} tiles"),
+    ("log.used", "{0} used {1}"),
+    ("log.used_on", "{0} used {1} on {2}"),
+    ("log.damage", "{0} damage"),
+    ("log.healed", "healed {0}"),
+    ("log.pushed", "pushed {0}"),
+    ("log.killed", "killed"),
+    ("log.died", "{0} died"),
+    ("log.detail", "{0} ({1})"),
+    ("log.first_separator", ": "),
+    ("log.separator", ", "),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("loading.message", "Chargement des ressources... Veuillez patienter"),
+    ("loading.failed", "Impossible de charger des ressources indispensables :"),
+    ("loading.replaced", "Des ressources sont manquantes et ont été remplacées :"),
+    ("loading.placeholder", "{0} (remplacée)"),
+    ("loading.missing", "{0} (manquante)"),
+    ("button.next_turn", "Tour suivant"),
+    ("button.pause", "Pause"),
+    ("menu.title", "Menu principal"),
+    ("menu.continue", "Continuer"),
+    ("menu.new_game", "Nouvelle partie"),
+    ("menu.scenarios", "Scénarios"),
+    ("menu.settings", "Paramètres"),
+    ("menu.back", "Retour"),
+    ("menu.main_menu", "Menu principal"),
+    ("pause.title", "Pause"),
+    ("pause.resume", "Reprendre"),
+    ("settings.title", "Paramètres"),
+    ("settings.master_volume", "Volume"),
+    ("settings.music_volume", "Musique"),
+    ("settings.effects_volume", "Effets sonores"),
+    ("settings.muted", "Couper le son"),
+    ("settings.language", "Langue : {0}"),
+    ("settings.scroll_speed", "Vitesse de défilement"),
+    ("settings.margin_ratio", "Largeur du panneau latéral"),
+    ("settings.animation_speed", "Vitesse des animations"),
+    ("settings.controls", "Commandes"),
+    ("settings.press_key", "Appuyez sur une touche"),
+    ("settings.unbound", "-"),
+    ("command.next_unit", "Unité suivante"),
+    ("command.previous_unit", "Unité précédente"),
+    ("command.first_attack", "Première attaque"),
+    ("command.second_attack", "Seconde attaque"),
+    ("command.next_turn", "Tour suivant"),
+    ("command.deselect", "Désélectionner"),
+    ("command.center_camera", "Centrer la caméra"),
+    ("command.cursor_top_left", "Curseur en haut à gauche"),
+    ("command.cursor_top_right", "Curseur en haut à droite"),
+    ("command.cursor_right", "Curseur à droite"),
+    ("command.cursor_bottom_right", "Curseur en bas à droite"),
+    ("command.cursor_bottom_left", "Curseur en bas à gauche"),
+    ("command.cursor_left", "Curseur à gauche"),
+    ("command.activate_cursor", "Activer le curseur"),
+    ("command.pause", "Pause"),
+    ("language.name", "Français"),
+    ("scenario.first_blood", "Premier sang"),
+    ("scenario.ambush", "Embuscade"),
+    ("results.victory", "Victoire"),
+    ("results.defeat", "Défaite"),
+    ("results.turns", "La bataille a duré {0} tours"),
+    ("results.play_again", "Rejouer"),
+    ("attack.stick_knock", "Coup de bâton"),
+    ("attack.stick_knock.description", "Frappe une unité adjacente (1 dégât) et la repousse."),
+    ("attack.volley_of_arrows", "Volée de flèches"),
+    ("attack.volley_of_arrows.description", "Tire des flèches dans une direction. Le premier ennemi dans cette direction subit 1 dégât et est repoussé."),
+    ("attack.offensive_sword_fight", "Assaut à l'épée"),
+    ("attack.offensive_sword_fight.description", "Attaque une unité adjacente à l'épée (2 dégâts) et l'attire (1 dégât pour les deux unités)."),
+    ("attack.defensive_sword_fight", "Parade à l'épée"),
+    ("attack.defensive_sword_fight.description", "Attaque une unité adjacente à l'épée (2 dégâts) et la repousse."),
+    ("attack.heal", "Soin"),
+    ("attack.heal.description", "Rend 1 PV. L'unité soignée retrouve au moins le tiers de ses PV maximum."),
+    ("unit.archer", "Archer"),
+    ("unit.knight", "Chevalier"),
+    ("unit.scout", "Éclaireur"),
+    ("unit.barbarian", "Barbare"),
+    ("unit.armored_barbarian", "Barbare en armure"),
+    ("unit.barbarian_lord_of_death", "Barbare seigneur de la mort"),
+    ("tile.grassy_plain", "Plaine herbeuse"),
+    ("tile.forest", "Forêt"),
+    ("tile.plain", "Plaine"),
+    ("tile.rocks", "Rochers"),
+    ("tile.rule.cost", "La traverser coûte 1 déplacement."),
+    ("tile.rule.hazard", "Les unités poussées dessus subissent des dégâts."),
+    ("direction.top_left", "Haut gauche"),
+    ("direction.top_right", "Haut droite"),
+    ("direction.right", "Droite"),
+    ("direction.bottom_right", "Bas droite"),
+    ("direction.bottom_left", "Bas gauche"),
+    ("direction.left", "Gauche"),
+    ("panel.life", "Vie : {0}/{1}"),
+    ("panel.moves", "Déplacements : {0}/{1}"),
+    ("panel.action_available", "Action disponible"),
+    ("panel.action_used", "Action utilisée"),
+    ("panel.will_use", "Va utiliser {0}"),
+    ("panel.toward", "{0} vers {1}"),
+    ("panel.then", " puis "),
+    ("panel.order", "{0} (agit en n°{1})"),
+    ("log.turn", "Tour {0}"),
+    ("log.moved", "{0} s'est déplacé de {1} cases"),
+    ("log.used", "{0} a utilisé {1}"),
+    ("log.used_on", "{0} a utilisé {1} sur {2}"),
+    ("log.damage", "{0} dégâts"),
+    ("log.healed", "soigné de {0}"),
+    ("log.pushed", "poussé vers {0}"),
+    ("log.killed", "tué"),
+    ("log.died", "{0} est mort"),
+    ("log.detail", "{0} ({1})"),
+    ("log.first_separator", " : "),
+    ("log.separator", ", "),
+];
+
+pub static CATALOGS: [Catalog; 2] = [
+    Catalog {
+        locale: "en",
+        messages: EN,
+    },
+    Catalog {
+        locale: "fr",
+        messages: FR,
+    },
+];
+
+thread_local! {
+    // the catalogs searched for a message, by order of preference
+    static FALLBACKS: RefCell<Vec<&'static Catalog>> = RefCell::new(get_fallbacks(DEFAULT_LOCALE));
+}
+
+fn find_catalog(locale: &str) -> Option<&'static Catalog> {
+    CATALOGS
+        .iter()
+        .find(|catalog| catalog.locale.eq_ignore_ascii_case(locale))
+}
+
+// "fr-CA" falls back on "fr" and then on the default locale
+pub fn get_fallbacks(locale: &str) -> Vec<&'static Catalog> {
+    let language = locale.split(|c| c == '-' || c == '_').next().unwrap_or("");
+    let mut fallbacks = Vec::new();
+    for locale in &[locale, language, DEFAULT_LOCALE] {
+        if let Some(catalog) = find_catalog(locale) {
+            if !fallbacks
+                .iter()
+                .any(|c: &&Catalog| c.locale == catalog.locale)
+            {
+                fallbacks.push(catalog);
+            }
+        }
+    }
+    fallbacks
+}
+
+// the preferred catalog, used by the settings to show the current language
+pub fn get_catalog() -> &'static Catalog {
+    FALLBACKS.with(|fallbacks| fallbacks.borrow()[0])
+}
+
+pub fn set_locale(locale: &str) {
+    FALLBACKS.with(|fallbacks| *fallbacks.borrow_mut() = get_fallbacks(locale));
+}
+
+// the preferred language of the user, such as "fr-FR"
+pub fn get_browser_locale() -> Option<String> {
+    web_sys::window()?.navigator().language()
+}
+
+// the key is returned when no catalog knows the message, the keys may come from the data files
+pub fn tr(key: &str) -> &str {
+    FALLBACKS.with(|fallbacks| {
+        fallbacks
+            .borrow()
+            .iter()
+            .find_map(|catalog| catalog.get(key))
+            .unwrap_or(key)
+    })
+}
+
+pub fn tr_args(key: &str, args: &[&dyn Display]) -> String {
+    interpolate(tr(key), args)
+}
+
+pub fn interpolate(template: &str, args: &[&dyn Display]) -> String {
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        message.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let argument = rest
+            .find('}')
+            .and_then(|end| rest[1..end].parse::<usize>().ok().map(|idx| (idx, end)))
+            .and_then(|(idx, end)| args.get(idx).map(|arg| (arg, end)));
+        match argument {
+            Some((arg, end)) => {
+                message.push_str(&arg.to_string());
+                rest = &rest[end + 1..];
+            }
+            None => {
+                message.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    message.push_str(rest);
+    message
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn catalogs() {
+        for catalog in &CATALOGS {
+            for (key, _message) in catalog.messages {
+                assert!(CATALOGS[0].get(key).is_some(), "{} is not in english", key);
+            }
+        }
+
+        assert_eq!(tr("unit.knight"), "Knight");
+        set_locale("fr-CA");
+        assert_eq!(tr("unit.knight"), "Chevalier");
+        assert_eq!(tr("unknown.key"), "unknown.key");
+        assert_eq!(
+            get_fallbacks("FR_fr")
+                .iter()
+                .map(|catalog| catalog.locale)
+                .collect::<Vec<_>>(),
+            vec!["fr", "en"]
+        );
+        set_locale("de");
+        assert_eq!(tr("unit.knight"), "Knight");
+    }
+
+    #[test]
+    fn interpolation() {
+        assert_eq!(interpolate("{0} damage", &[&3]), "3 damage");
+        assert_eq!(
+            interpolate("{1} used {0}", &[&"Heal", &"Scout"]),
+            "Scout used Heal"
+        );
+        assert_eq!(interpolate("{2} {x} {", &[&1]), "{2} {x} {");
+    }
+}
@@ -280,21 +280,52 @@ impl HexIndex {
         (x, y)
     }
 
-    pub fn from_canvas_coords(mut coords: (isize, isize)) -> Option<HexIndex> {
-        coords.1 -= 160;
-        coords.1 -= coords.1 % 193;
-        coords.1 /= 193;
-        coords.0 -= match coords.1 {
-            0 | 8 => 506,
-            1 | 7 => 380,
-            2 | 6 => 253,
-            3 | 5 => 127,
-            _ => 0,
-        };
-        coords.0 -= coords.0 % 253;
-        coords.0 /= 253;
-        (coords.0 as usize, coords.1 as usize).try_into().ok()
+    // center of the top face of the tile, in internal canvas coordinates
+    pub fn get_canvas_center(&self) -> (f64, f64) {
+        let (x, y) = self.get_canvas_coords();
+        (x as f64 + 128.0, y as f64 + 256.0)
     }
+
+    // get the hexagon containing a point
+    pub fn from_canvas_coords(coords: (isize, isize)) -> Option<HexIndex> {
+        let coords = (coords.0 as f64, coords.1 as f64);
+        let row = ((coords.1 - 256.0) / 193.0).round() as isize;
+
+        // in a hexagonal grid, a point is inside the hexagon with the nearest center
+        let mut nearest: Option<((isize, isize), f64)> = None;
+        for y in row - 1..=row + 1 {
+            let x = ((coords.0 - 128.0 - get_row_offset(y)) / 253.0).round() as isize;
+            let center = (
+                x as f64 * 253.0 + get_row_offset(y) + 128.0,
+                y as f64 * 193.0 + 256.0,
+            );
+            let relative = normalize(coords, center);
+            let distance = relative.0 * relative.0 + relative.1 * relative.1;
+            if nearest.map_or(true, |(_, d)| distance < d) {
+                nearest = Some(((x, y), distance));
+            }
+        }
+
+        let ((x, y), _distance) = nearest?;
+        if x < 0 || y < 0 {
+            return None;
+        }
+        (x as usize, y as usize).try_into().ok()
+    }
+}
+
+// the horizontal offset of a row, rows outside of the board included
+fn get_row_offset(y: isize) -> f64 {
+    ((y - 4).abs() * 128) as f64
+}
+
+// transform internal canvas coordinates into coordinates relative to a center
+// in a grid of regular hexagons with a radius of 1 (tiles are flattened on the canvas)
+fn normalize(coords: (f64, f64), center: (f64, f64)) -> (f64, f64) {
+    (
+        (coords.0 - center.0) * 3f64.sqrt() / 253.0,
+        (coords.1 - center.1) * 1.5 / 193.0,
+    )
 }
 
 impl std::convert::TryFrom<usize> for HexIndex {
@@ -489,4 +520,29 @@ mod test {
             top_top_left
         );
     }
+
+    #[test]
+    fn picking() {
+        let middle: HexIndex = (4, 4).try_into().unwrap();
+        let (x, y) = middle.get_canvas_center();
+        let pick =
+            |dx: f64, dy: f64| HexIndex::from_canvas_coords(((x + dx) as isize, (y + dy) as isize));
+
+        assert_eq!(pick(0.0, 0.0), Some(middle));
+        assert_eq!(pick(100.0, 0.0), Some(middle));
+        assert_eq!(pick(0.0, 100.0), Some(middle));
+
+        // inside the bounding box of the tile but above its top right edge
+        assert_eq!(pick(110.0, -90.0), middle.get_top_right_neighbour());
+        assert_eq!(pick(110.0, -60.0), Some(middle));
+
+        // outside of the board
+        let corner: HexIndex = 0.try_into().unwrap();
+        let (x, y) = corner.get_canvas_center();
+        assert_eq!(
+            HexIndex::from_canvas_coords((x as isize - 110, y as isize - 90)),
+            None
+        );
+        assert_eq!(HexIndex::from_canvas_coords((-500, -500)), None);
+    }
 }
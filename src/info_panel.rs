@@ -0,0 +1,130 @@
+use crate::{i18n::*, units::*};
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::{canvas::*, color::*, drawable::*, image::*};
+
+const BOX_STYLE: LineStyle = LineStyle {
+    cap: LineCap::Round,
+    join: LineJoin::Round,
+    size: 4.0,
+    color: Color {
+        alpha: 255,
+        red: 67,
+        green: 75,
+        blue: 96,
+    },
+};
+
+pub const PANEL_HEIGHT: f64 = 190.0;
+const PORTRAIT_SIZE: f64 = 80.0;
+const MARGIN: f64 = 10.0;
+const FONT_SIZE: f64 = 16.0;
+const LINE_HEIGHT: f64 = 20.0;
+
+// what a barbarian will do at the end of the turn
+pub fn describe_barbarian_action(unit: &Unit, order: Option<usize>) -> Option<String> {
+    let (attack, directions) = unit.barbarian_next_action.as_ref()?;
+    let mut description = tr_args("panel.will_use", &[&attack.get_name()]);
+    if !directions.is_empty() {
+        let directions: Vec<&str> = directions.iter().map(|d| d.get_name()).collect();
+        let directions = directions.join(tr("panel.then"));
+        description = tr_args("panel.toward", &[&description, &directions]);
+    }
+    if let Some(order) = order {
+        description = tr_args("panel.order", &[&description, &order]);
+    }
+    Some(description)
+}
+
+// stats and status of a unit
+pub fn get_lines(unit: &Unit, order: Option<usize>) -> Vec<String> {
+    let mut lines = vec![
+        tr_args(
+            "panel.life",
+            &[&unit.life.get_current(), &unit.life.get_max()],
+        ),
+        tr_args(
+            "panel.moves",
+            &[&unit.remaining_moves, &unit.unit_type.moves_per_turn()],
+        ),
+        String::from(if unit.action_remaining {
+            tr("panel.action_available")
+        } else {
+            tr("panel.action_used")
+        }),
+    ];
+    if let Some(description) = describe_barbarian_action(unit, order) {
+        lines.push(description);
+    }
+    lines
+}
+
+// order: position of the barbarian in the resolution order of this turn
+pub fn draw_unit_info(
+    mut canvas: &mut Canvas,
+    unit: &Unit,
+    portrait: &Image,
+    coords: (f64, f64),
+    width: f64,
+    order: Option<usize>,
+) {
+    BOX_STYLE.apply_on_canvas(&mut canvas);
+    let context = canvas.get_2d_canvas_rendering_context();
+    context.begin_path();
+    context.stroke_rect(coords.0, coords.1, width, PANEL_HEIGHT);
+    context.set_fill_style(&JsValue::from_str("rgb(24, 28, 39)"));
+    context.fill_rect(coords.0, coords.1, width, PANEL_HEIGHT);
+    context.stroke();
+
+    context
+        .draw_image_with_html_image_element_and_dw_and_dh(
+            portrait.get_html_element(),
+            coords.0 + MARGIN,
+            coords.1 + MARGIN,
+            PORTRAIT_SIZE,
+            PORTRAIT_SIZE,
+        )
+        .unwrap();
+
+    context.set_fill_style(&JsValue::from_str("white"));
+    context.set_font(&format!("bold {}px Arial", FONT_SIZE * 1.25));
+    context
+        .fill_text_with_max_width(
+            unit.unit_type.get_name(),
+            coords.0 + PORTRAIT_SIZE + MARGIN * 2.0,
+            coords.1 + MARGIN + FONT_SIZE * 1.25,
+            width - PORTRAIT_SIZE - MARGIN * 3.0,
+        )
+        .unwrap();
+
+    context.set_font(&format!("{}px Arial", FONT_SIZE));
+    let mut y = coords.1 + MARGIN + PORTRAIT_SIZE;
+    for line in get_lines(unit, order) {
+        y += LINE_HEIGHT;
+        context
+            .fill_text_with_max_width(&line, coords.0 + MARGIN, y, width - MARGIN * 2.0)
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{actions::Attack, map::Direction};
+
+    #[test]
+    fn barbarian_intent() {
+        let mut unit = Unit::new(UnitType::Barbarian);
+        assert_eq!(describe_barbarian_action(&unit, None), None);
+
+        unit.barbarian_next_action = Some((
+            Attack::StickKnock,
+            vec![Direction::TopRight, Direction::Right],
+        ));
+        assert_eq!(
+            describe_barbarian_action(&unit, Some(2)),
+            Some(String::from(
+                "Will use Stick Knock toward Top Right then Right (acts #2)"
+            ))
+        );
+    }
+}
@@ -0,0 +1,562 @@
+use crate::{
+    camera::Drag, frame::FIXED_STEP, idx::HexIndex, keybindings::*, map::Direction,
+    preferences::Preferences, touch::*, viewport::*, widget::*,
+};
+use std::cell::RefCell;
+use std::convert::TryInto;
+use wasm_game_lib::graphics::{canvas::*, color::*, drawable::*};
+use wasm_game_lib::inputs::{event::Event, keyboard::*, mouse::*};
+
+const HOLD_DURATION: f64 = 500.0; // in milliseconds
+const TAP_DISTANCE: f64 = 10.0; // a finger moving further is not a tap
+
+const CURSOR_STYLE: LineStyle = LineStyle {
+    cap: LineCap::Round,
+    join: LineJoin::Round,
+    size: 6.0,
+    color: Color {
+        alpha: 255,
+        red: 255,
+        green: 220,
+        blue: 80,
+    },
+};
+
+// corners of the top face of a tile, relative to its center
+const CURSOR_CORNERS: [(f64, f64); 6] = [
+    (0.0, -128.0),
+    (126.0, -64.0),
+    (126.0, 64.0),
+    (0.0, 128.0),
+    (-126.0, 64.0),
+    (-126.0, -64.0),
+];
+
+// what the player wants to do, independently of the device used
+#[derive(Clone, PartialEq, Debug)]
+pub enum Intent {
+    SelectHex(HexIndex),
+    HoverHex(HexIndex),
+    HoverNone,            // the pointer left the board
+    InspectHex(HexIndex), // show the details of the unit without selecting it
+    ChooseAttack(bool),   // false for the first attack, true for the second one
+    EndTurn,
+    Cancel,
+    CycleUnits(bool), // true for the next unit, false for the previous one
+    Pause,
+}
+
+impl Intent {
+    // other intents are delayed while the consequences of the last action are animated
+    pub fn is_immediate(&self) -> bool {
+        matches!(
+            self,
+            Intent::HoverHex(_) | Intent::HoverNone | Intent::InspectHex(_) | Intent::Pause
+        )
+    }
+}
+
+// what happened to a pointer event sent to the widgets
+#[derive(Clone, PartialEq, Debug)]
+pub enum Routed {
+    Ignored, // the event goes to the board
+    Consumed,
+    Intent(Intent),
+}
+
+// what the input layer needs to know about the game
+pub trait InputTarget {
+    // the widgets see pointer events before the board
+    fn route_ui_event(&mut self, event: &UiEvent) -> Routed;
+    fn get_selected_position(&self) -> Option<HexIndex>;
+}
+
+pub struct InputHandler<'a> {
+    viewport: &'a RefCell<Viewport>,
+    preferences: &'a RefCell<Preferences>, // read on use so the changes apply immediately
+    arrows: (bool, bool, bool, bool),
+    shift: bool,
+    drag: Drag,
+    cursor: Option<HexIndex>, // hex selected with the keyboard
+    cursor_moved: bool,       // since the last call to poll_cursor_moved
+    hovered_hex: Option<HexIndex>,
+    intents: Vec<Intent>,
+    clock: f64,
+    touches: Vec<(f64, f64)>,
+    touch_start: Option<(f64, (f64, f64))>, // (time, position) of a possible tap or hold
+}
+
+impl<'a> InputHandler<'a> {
+    pub fn new(
+        viewport: &'a RefCell<Viewport>,
+        preferences: &'a RefCell<Preferences>,
+    ) -> InputHandler<'a> {
+        InputHandler {
+            viewport,
+            preferences,
+            arrows: (false, false, false, false),
+            shift: false,
+            drag: Drag::default(),
+            cursor: None,
+            cursor_moved: false,
+            hovered_hex: None,
+            intents: Vec::new(),
+            clock: 0.0,
+            touches: Vec::new(),
+            touch_start: None,
+        }
+    }
+
+    // used by tests and replays to bypass the devices
+    pub fn push(&mut self, intent: Intent) {
+        self.intents.push(intent);
+    }
+
+    pub fn poll_intents(&mut self) -> Vec<Intent> {
+        self.intents.split_off(0)
+    }
+
+    // true if the keyboard cursor moved or was hidden since the last call
+    pub fn poll_cursor_moved(&mut self) -> bool {
+        std::mem::replace(&mut self.cursor_moved, false)
+    }
+
+    fn set_cursor(&mut self, cursor: Option<HexIndex>) {
+        if cursor != self.cursor {
+            self.cursor = cursor;
+            self.cursor_moved = true;
+        }
+    }
+
+    fn hover(&mut self, index: Option<HexIndex>) {
+        if index != self.hovered_hex {
+            self.hovered_hex = index;
+            match index {
+                Some(index) => self.push(Intent::HoverHex(index)),
+                None => self.push(Intent::HoverNone),
+            }
+        }
+    }
+
+    fn handle_command(&mut self, command: Command, target: &mut dyn InputTarget) {
+        match command {
+            Command::NextUnit | Command::PreviousUnit => {
+                self.set_cursor(None);
+                self.push(Intent::CycleUnits(command == Command::NextUnit));
+            }
+            Command::FirstAttack => self.push(Intent::ChooseAttack(false)),
+            Command::SecondAttack => self.push(Intent::ChooseAttack(true)),
+            Command::NextTurn => self.push(Intent::EndTurn),
+            Command::Deselect => self.push(Intent::Cancel),
+            Command::Pause => self.push(Intent::Pause),
+            Command::CenterCamera => {
+                if let Some(position) = target.get_selected_position() {
+                    self.viewport.borrow_mut().center_camera_on(&position);
+                }
+            }
+            Command::MoveCursor(direction) => {
+                // the cursor starts on the selected unit or in the middle of the board
+                let cursor = match self.cursor {
+                    Some(cursor) => cursor
+                        .get_neighbour(&Direction::iter()[direction])
+                        .unwrap_or(cursor),
+                    None => target
+                        .get_selected_position()
+                        .unwrap_or_else(|| 30.try_into().unwrap()),
+                };
+                self.set_cursor(Some(cursor));
+                self.hover(Some(cursor));
+            }
+            Command::ActivateCursor => {
+                if let Some(cursor) = self.cursor {
+                    self.push(Intent::SelectHex(cursor));
+                }
+            }
+        }
+    }
+
+    // the widgets are above the board so they get the click first
+    // returns the clicked hex when no widget used the click
+    // padding: extra space around the widgets, used for touch input
+    fn click(
+        &mut self,
+        (x, y): (u32, u32),
+        padding: f64,
+        target: &mut dyn InputTarget,
+    ) -> Option<HexIndex> {
+        match target.route_ui_event(&UiEvent::Click((x as f64, y as f64), padding)) {
+            Routed::Intent(intent) => {
+                self.push(intent);
+                None
+            }
+            Routed::Consumed => None,
+            Routed::Ignored => self.viewport.borrow().screen_to_hex(x, y),
+        }
+    }
+
+    pub fn handle_event(&mut self, event: Event, target: &mut dyn InputTarget) {
+        match event {
+            Event::KeyboardEvent(ke) => match ke {
+                KeyboardEvent::Down(key) => match key {
+                    Key::UpArrow => self.arrows.0 = true,
+                    Key::RightArrow => self.arrows.1 = true,
+                    Key::DownArrow => self.arrows.2 = true,
+                    Key::LeftArrow => self.arrows.3 = true,
+                    Key::Shift => self.shift = true,
+                    key => {
+                        let command = self
+                            .preferences
+                            .borrow()
+                            .keybindings
+                            .get_command(key, self.shift);
+                        if let Some(command) = command {
+                            self.handle_command(command, target);
+                        }
+                    }
+                },
+                KeyboardEvent::Up(key) => match key {
+                    Key::UpArrow => self.arrows.0 = false,
+                    Key::RightArrow => self.arrows.1 = false,
+                    Key::DownArrow => self.arrows.2 = false,
+                    Key::LeftArrow => self.arrows.3 = false,
+                    Key::Shift => self.shift = false,
+                    _ => (),
+                },
+            },
+            Event::MouseEvent(me) => match me {
+                MouseEvent::Move(x, y) => {
+                    let pressed = is_mouse_pressed();
+                    let movement = self.drag.handle_mouse_move(x, y, pressed);
+                    let event = UiEvent::PointerMove((x as f64, y as f64), pressed);
+                    if target.route_ui_event(&event) != Routed::Ignored {
+                        // the board is below the widgets
+                        self.hover(None);
+                        return;
+                    }
+                    if let Some(movement) = movement {
+                        let mut viewport = self.viewport.borrow_mut();
+                        if x as usize > viewport.margin {
+                            viewport.move_camera(movement);
+                        }
+                    }
+                    let hovered_hex = self.viewport.borrow().screen_to_hex(x, y);
+                    self.hover(hovered_hex);
+                }
+                MouseEvent::Click(x, y) => {
+                    if self.drag.is_drag() {
+                        // the player was moving the camera
+                        return;
+                    }
+                    // the keyboard cursor is hidden when the mouse is used
+                    self.set_cursor(None);
+                    if let Some(index) = self.click((x, y), 0.0, target) {
+                        self.push(Intent::SelectHex(index));
+                    }
+                }
+                MouseEvent::Scroll(_delta_x, delta_y, _delta_z, _delta_mode) => {
+                    let mouse_position = get_mouse_position();
+                    let position = (mouse_position.0 as f64, mouse_position.1 as f64);
+                    if target.route_ui_event(&UiEvent::Scroll(position, delta_y)) != Routed::Ignored
+                    {
+                        return;
+                    }
+                    if mouse_position.0 as usize > self.viewport.borrow().margin {
+                        self.viewport
+                            .borrow_mut()
+                            .zoom_camera(mouse_position, delta_y < 0.0);
+                    }
+                }
+                _ => (),
+            },
+            event => log!("{:?}", event),
+        }
+    }
+
+    // one finger taps and holds, two fingers pan and pinch
+    pub fn handle_touch(&mut self, input: TouchInput, target: &mut dyn InputTarget) {
+        match input.kind {
+            TouchKind::Start if input.touches.len() == 1 => {
+                self.touch_start = Some((self.clock, input.touches[0]));
+                // shows the widget under the finger as pressed
+                target.route_ui_event(&UiEvent::PointerMove(input.touches[0], true));
+            }
+            TouchKind::Start => self.touch_start = None,
+            TouchKind::Move if input.touches.len() >= 2 && self.touches.len() >= 2 => {
+                let (old_center, old_distance) = get_pinch(&self.touches);
+                let (center, distance) = get_pinch(&input.touches);
+                let mut viewport = self.viewport.borrow_mut();
+                viewport.move_camera((center.0 - old_center.0, center.1 - old_center.1));
+                if old_distance > 0.0 {
+                    viewport.zoom_camera_by(center, distance / old_distance);
+                }
+            }
+            TouchKind::Move if input.touches.len() == 1 => {
+                let (x, y) = input.touches[0];
+                if let Some((_time, start)) = self.touch_start {
+                    if get_distance(start, (x, y)) > TAP_DISTANCE {
+                        self.touch_start = None;
+                    }
+                }
+                if target.route_ui_event(&UiEvent::PointerMove((x, y), true)) == Routed::Ignored {
+                    let hovered_hex = self.viewport.borrow().screen_to_hex(x as u32, y as u32);
+                    self.hover(hovered_hex);
+                } else {
+                    self.hover(None);
+                }
+            }
+            TouchKind::Move => (),
+            TouchKind::End => {
+                if let (Some((_time, (x, y))), true) = (self.touch_start, input.touches.is_empty())
+                {
+                    self.set_cursor(None);
+                    let padding = self.viewport.borrow().get_touch_padding();
+                    if let Some(index) = self.click((x as u32, y as u32), padding, target) {
+                        self.hover(Some(index));
+                        self.push(Intent::SelectHex(index));
+                    }
+                }
+                if input.touches.is_empty() {
+                    // no finger is left to hover or press the widgets
+                    target.route_ui_event(&UiEvent::PointerLeave);
+                }
+                self.touch_start = None;
+            }
+        }
+        self.touches = input.touches;
+    }
+
+    // delta: time since the last update in milliseconds
+    pub fn update(&mut self, delta: f64) {
+        self.clock += delta;
+
+        // a finger held without moving inspects the hex instead of selecting it
+        if let Some((time, (x, y))) = self.touch_start {
+            if self.clock - time >= HOLD_DURATION {
+                self.touch_start = None;
+                let held_hex = self.viewport.borrow().screen_to_hex(x as u32, y as u32);
+                if let Some(index) = held_hex {
+                    self.push(Intent::InspectHex(index));
+                }
+            }
+        }
+
+        // move the camera while the arrows are pressed
+        // the scroll speed is given for a fixed step
+        let speed = self.preferences.borrow().scroll_speed * delta / FIXED_STEP;
+        let mut viewport = self.viewport.borrow_mut();
+        if self.arrows.0 {
+            viewport.move_camera((0.0, speed));
+        }
+        if self.arrows.1 {
+            viewport.move_camera((-speed, 0.0));
+        }
+        if self.arrows.2 {
+            viewport.move_camera((0.0, -speed));
+        }
+        if self.arrows.3 {
+            viewport.move_camera((speed, 0.0));
+        }
+    }
+}
+
+fn get_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+// the center and the distance between the first two fingers
+fn get_pinch(touches: &[(f64, f64)]) -> ((f64, f64), f64) {
+    let (a, b) = (touches[0], touches[1]);
+    (((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0), get_distance(a, b))
+}
+
+impl<'a> Drawable for InputHandler<'a> {
+    fn draw_on_canvas(&self, mut canvas: &mut Canvas) {
+        let cursor = match self.cursor {
+            Some(cursor) => cursor,
+            None => return,
+        };
+        let viewport = *self.viewport.borrow();
+        let area = viewport.get_board_area();
+        let (x, y) = cursor.get_canvas_center();
+
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.save();
+        context.begin_path();
+        context.rect(area.0, area.1, area.2, area.3);
+        context.clip();
+        context.begin_path();
+        for (dx, dy) in CURSOR_CORNERS.iter() {
+            let (x, y) = viewport.board_to_screen((x + dx) as isize, (y + dy) as isize);
+            context.line_to(x as f64, y as f64);
+        }
+        context.close_path();
+        CURSOR_STYLE.apply_on_canvas(&mut canvas);
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.stroke();
+        context.restore();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct FakeTarget;
+
+    // a next turn button covers the left of the screen
+    impl InputTarget for FakeTarget {
+        fn route_ui_event(&mut self, event: &UiEvent) -> Routed {
+            match event {
+                UiEvent::Click((x, _y), _padding) if *x < 100.0 => Routed::Intent(Intent::EndTurn),
+                UiEvent::PointerMove((x, _y), _pressed) if *x < 100.0 => Routed::Consumed,
+                _ => Routed::Ignored,
+            }
+        }
+
+        fn get_selected_position(&self) -> Option<HexIndex> {
+            None
+        }
+    }
+
+    fn press(input: &mut InputHandler, key: Key) {
+        input.handle_event(
+            Event::KeyboardEvent(KeyboardEvent::Down(key)),
+            &mut FakeTarget,
+        );
+        input.handle_event(
+            Event::KeyboardEvent(KeyboardEvent::Up(key)),
+            &mut FakeTarget,
+        );
+    }
+
+    #[test]
+    fn keyboard_intents() {
+        let viewport = RefCell::new(Viewport::new((1500, 800)));
+        let preferences = RefCell::new(Preferences::default());
+        let mut input = InputHandler::new(&viewport, &preferences);
+
+        press(&mut input, Key::Tab);
+        input.handle_event(
+            Event::KeyboardEvent(KeyboardEvent::Down(Key::Shift)),
+            &mut FakeTarget,
+        );
+        press(&mut input, Key::Tab);
+        input.handle_event(
+            Event::KeyboardEvent(KeyboardEvent::Up(Key::Shift)),
+            &mut FakeTarget,
+        );
+        press(&mut input, Key::Digit2);
+        press(&mut input, Key::Escape);
+        press(&mut input, Key::Enter);
+        assert_eq!(
+            input.poll_intents(),
+            vec![
+                Intent::CycleUnits(true),
+                Intent::CycleUnits(false),
+                Intent::ChooseAttack(true),
+                Intent::Cancel,
+                Intent::EndTurn
+            ]
+        );
+
+        // the cursor starts in the middle of the board
+        let middle: HexIndex = 30.try_into().unwrap();
+        assert!(!input.poll_cursor_moved());
+        press(&mut input, Key::D);
+        press(&mut input, Key::D);
+        press(&mut input, Key::Space);
+        assert!(input.poll_cursor_moved());
+        assert!(!input.poll_cursor_moved());
+        assert_eq!(
+            input.poll_intents(),
+            vec![
+                Intent::HoverHex(middle),
+                Intent::HoverHex(middle.get_right_neighbour().unwrap()),
+                Intent::SelectHex(middle.get_right_neighbour().unwrap())
+            ]
+        );
+    }
+
+    #[test]
+    fn mouse_intents() {
+        let viewport = RefCell::new(Viewport::new((1500, 800)));
+        let preferences = RefCell::new(Preferences::default());
+        let mut input = InputHandler::new(&viewport, &preferences);
+
+        let index: HexIndex = 12.try_into().unwrap();
+        let (x, y) = index.get_canvas_center();
+        let (x, y) = viewport.borrow().board_to_screen(x as isize, y as isize);
+        input.handle_event(
+            Event::MouseEvent(MouseEvent::Click(x as u32, y as u32)),
+            &mut FakeTarget,
+        );
+        input.handle_event(
+            Event::MouseEvent(MouseEvent::Click(10, 10)),
+            &mut FakeTarget,
+        );
+        input.push(Intent::Cancel);
+        assert_eq!(
+            input.poll_intents(),
+            vec![Intent::SelectHex(index), Intent::EndTurn, Intent::Cancel]
+        );
+    }
+
+    #[test]
+    fn touch_intents() {
+        let viewport = RefCell::new(Viewport::new((1500, 800)));
+        let preferences = RefCell::new(Preferences::default());
+        let mut input = InputHandler::new(&viewport, &preferences);
+        let index: HexIndex = 12.try_into().unwrap();
+        let (x, y) = index.get_canvas_center();
+        let (x, y) = viewport.borrow().board_to_screen(x as isize, y as isize);
+        let position = (x as f64, y as f64);
+        let touch = |kind, touches: &[(f64, f64)]| TouchInput {
+            kind,
+            touches: touches.to_vec(),
+        };
+
+        // tap
+        input.handle_touch(touch(TouchKind::Start, &[position]), &mut FakeTarget);
+        input.update(100.0);
+        input.handle_touch(touch(TouchKind::End, &[]), &mut FakeTarget);
+        assert_eq!(
+            input.poll_intents(),
+            vec![Intent::HoverHex(index), Intent::SelectHex(index)]
+        );
+
+        // the widgets are tapped before the board
+        input.handle_touch(touch(TouchKind::Start, &[(10.0, 10.0)]), &mut FakeTarget);
+        input.handle_touch(touch(TouchKind::End, &[]), &mut FakeTarget);
+        assert_eq!(input.poll_intents(), vec![Intent::EndTurn]);
+
+        // hold
+        input.handle_touch(touch(TouchKind::Start, &[position]), &mut FakeTarget);
+        input.update(600.0);
+        input.handle_touch(touch(TouchKind::End, &[]), &mut FakeTarget);
+        assert_eq!(input.poll_intents(), vec![Intent::InspectHex(index)]);
+
+        // pinch
+        let center = (900.0, 400.0);
+        input.handle_touch(
+            touch(TouchKind::Start, &[(center.0 - 50.0, center.1)]),
+            &mut FakeTarget,
+        );
+        input.handle_touch(
+            touch(
+                TouchKind::Start,
+                &[(center.0 - 50.0, center.1), (center.0 + 50.0, center.1)],
+            ),
+            &mut FakeTarget,
+        );
+        input.handle_touch(
+            touch(
+                TouchKind::Move,
+                &[(center.0 - 100.0, center.1), (center.0 + 100.0, center.1)],
+            ),
+            &mut FakeTarget,
+        );
+        input.handle_touch(touch(TouchKind::End, &[]), &mut FakeTarget);
+        assert!(viewport.borrow().camera.zoom > 1.9);
+        assert!(input.poll_intents().is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+use crate::storage::Storage;
+use wasm_game_lib::inputs::keyboard::Key;
+
+const STORAGE_KEY: &str = "keybindings";
+// in the order of Direction::iter(), they are saved so they must not be translated
+const CURSOR_NAMES: [&str; 6] = [
+    "Top Left",
+    "Top Right",
+    "Right",
+    "Bottom Right",
+    "Bottom Left",
+    "Left",
+];
+const CURSOR_LABELS: [&str; 6] = [
+    "command.cursor_top_left",
+    "command.cursor_top_right",
+    "command.cursor_right",
+    "command.cursor_bottom_right",
+    "command.cursor_bottom_left",
+    "command.cursor_left",
+];
+
+// keys that can be bound to a command, they are saved using their debug name
+const BINDABLE_KEYS: [Key; 40] = [
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Digit0,
+    Key::Digit1,
+    Key::Digit2,
+    Key::Digit3,
+    Key::Digit4,
+    Key::Digit5,
+    Key::Digit6,
+    Key::Digit7,
+    Key::Digit8,
+    Key::Digit9,
+    Key::Tab,
+    Key::Enter,
+    Key::Escape,
+    Key::Space,
+];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Command {
+    NextUnit,
+    PreviousUnit,
+    FirstAttack,
+    SecondAttack,
+    NextTurn,
+    Deselect,
+    CenterCamera,
+    MoveCursor(usize), // index in Direction::iter()
+    ActivateCursor,
+    Pause,
+}
+
+impl Command {
+    // Fake iter over all the commands
+    pub fn iter() -> Vec<Command> {
+        let mut commands = vec![
+            Command::NextUnit,
+            Command::PreviousUnit,
+            Command::FirstAttack,
+            Command::SecondAttack,
+            Command::NextTurn,
+            Command::Deselect,
+            Command::CenterCamera,
+        ];
+        commands.extend((0..6).map(Command::MoveCursor));
+        commands.push(Command::ActivateCursor);
+        commands.push(Command::Pause);
+        commands
+    }
+
+    pub fn get_name(self) -> String {
+        match self {
+            Command::MoveCursor(direction) => {
+                format!("Cursor {}", CURSOR_NAMES[direction % 6])
+            }
+            Command::NextUnit => String::from("Next unit"),
+            Command::PreviousUnit => String::from("Previous unit"),
+            Command::FirstAttack => String::from("First attack"),
+            Command::SecondAttack => String::from("Second attack"),
+            Command::NextTurn => String::from("Next turn"),
+            Command::Deselect => String::from("Deselect"),
+            Command::CenterCamera => String::from("Center camera"),
+            Command::ActivateCursor => String::from("Activate cursor"),
+            Command::Pause => String::from("Pause"),
+        }
+    }
+
+    // key of the translated name shown in the settings
+    pub fn get_label(self) -> &'static str {
+        match self {
+            Command::MoveCursor(direction) => CURSOR_LABELS[direction % 6],
+            Command::NextUnit => "command.next_unit",
+            Command::PreviousUnit => "command.previous_unit",
+            Command::FirstAttack => "command.first_attack",
+            Command::SecondAttack => "command.second_attack",
+            Command::NextTurn => "command.next_turn",
+            Command::Deselect => "command.deselect",
+            Command::CenterCamera => "command.center_camera",
+            Command::ActivateCursor => "command.activate_cursor",
+            Command::Pause => "command.pause",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Binding {
+    pub key: Key,
+    pub shift: bool,
+}
+
+impl Binding {
+    pub fn new(key: Key, shift: bool) -> Binding {
+        Binding { key, shift }
+    }
+
+    pub fn get_name(self) -> String {
+        if self.shift {
+            format!("Shift+{:?}", self.key)
+        } else {
+            format!("{:?}", self.key)
+        }
+    }
+
+    pub fn is_bindable(key: Key) -> bool {
+        BINDABLE_KEYS.contains(&key)
+    }
+
+    pub fn from_name(name: &str) -> Option<Binding> {
+        let (shift, name) = match name.strip_prefix("Shift+") {
+            Some(name) => (true, name),
+            None => (false, name),
+        };
+        BINDABLE_KEYS
+            .iter()
+            .find(|key| format!("{:?}", key) == name)
+            .map(|key| Binding::new(*key, shift))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Keybindings {
+    bindings: Vec<(Command, Binding)>,
+}
+
+impl Default for Keybindings {
+    fn default() -> Keybindings {
+        Keybindings {
+            bindings: vec![
+                (Command::NextUnit, Binding::new(Key::Tab, false)),
+                (Command::PreviousUnit, Binding::new(Key::Tab, true)),
+                (Command::FirstAttack, Binding::new(Key::Digit1, false)),
+                (Command::SecondAttack, Binding::new(Key::Digit2, false)),
+                (Command::NextTurn, Binding::new(Key::Enter, false)),
+                (Command::Deselect, Binding::new(Key::Escape, false)),
+                (Command::CenterCamera, Binding::new(Key::C, false)),
+                (Command::MoveCursor(0), Binding::new(Key::Q, false)),
+                (Command::MoveCursor(1), Binding::new(Key::E, false)),
+                (Command::MoveCursor(2), Binding::new(Key::D, false)),
+                (Command::MoveCursor(3), Binding::new(Key::X, false)),
+                (Command::MoveCursor(4), Binding::new(Key::Z, false)),
+                (Command::MoveCursor(5), Binding::new(Key::A, false)),
+                (Command::ActivateCursor, Binding::new(Key::Space, false)),
+                (Command::Pause, Binding::new(Key::P, false)),
+            ],
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn get_command(&self, key: Key, shift: bool) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|(_command, binding)| *binding == Binding::new(key, shift))
+            .map(|(command, _binding)| *command)
+    }
+
+    pub fn get_binding(&self, command: Command) -> Option<Binding> {
+        self.bindings
+            .iter()
+            .find(|(c, _binding)| *c == command)
+            .map(|(_command, binding)| *binding)
+    }
+
+    // a binding can only trigger one command, the previous command using it is unbound
+    pub fn bind(&mut self, command: Command, binding: Binding) {
+        self.bindings
+            .retain(|(c, b)| *c == command || *b != binding);
+        match self.bindings.iter_mut().find(|(c, _binding)| *c == command) {
+            Some((_command, b)) => *b = binding,
+            None => self.bindings.push((command, binding)),
+        }
+    }
+
+    // one "command=binding" line per command, the binding of unbound commands is empty
+    pub fn serialize(&self) -> String {
+        Command::iter()
+            .into_iter()
+            .map(|command| {
+                let binding = self
+                    .get_binding(command)
+                    .map(|binding| binding.get_name())
+                    .unwrap_or_default();
+                format!("{}={}", command.get_name(), binding)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // invalid lines are ignored and commands missing from the data keep their default binding
+    pub fn deserialize(data: &str) -> Keybindings {
+        let mut keybindings = Keybindings::default();
+        for line in data.lines() {
+            let mut parts = line.splitn(2, '=');
+            let (command, binding) = match (parts.next(), parts.next()) {
+                (Some(command), Some(binding)) => (command.trim(), binding.trim()),
+                _ => continue,
+            };
+            let command = Command::iter()
+                .into_iter()
+                .find(|c| c.get_name() == command);
+            match (command, Binding::from_name(binding)) {
+                (Some(command), Some(binding)) => keybindings.bind(command, binding),
+                (Some(command), None) if binding.is_empty() => {
+                    keybindings.bindings.retain(|(c, _binding)| *c != command)
+                }
+                _ => (),
+            }
+        }
+        keybindings
+    }
+
+    pub fn load(storage: &dyn Storage) -> Keybindings {
+        match storage.get_item(STORAGE_KEY) {
+            Some(data) => Keybindings::deserialize(&data),
+            None => Keybindings::default(),
+        }
+    }
+
+    pub fn save(&self, storage: &dyn Storage) {
+        if !storage.set_item(STORAGE_KEY, &self.serialize()) {
+            log!("failed to save keybindings");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn rebinding() {
+        let mut keybindings = Keybindings::default();
+        assert_eq!(
+            keybindings.get_command(Key::Tab, false),
+            Some(Command::NextUnit)
+        );
+        assert_eq!(
+            keybindings.get_command(Key::Tab, true),
+            Some(Command::PreviousUnit)
+        );
+
+        keybindings.bind(Command::NextTurn, Binding::new(Key::Tab, false));
+        assert_eq!(
+            keybindings.get_command(Key::Tab, false),
+            Some(Command::NextTurn)
+        );
+        assert_eq!(keybindings.get_command(Key::Enter, false), None);
+        assert_eq!(keybindings.get_binding(Command::NextUnit), None);
+    }
+
+    #[test]
+    fn persistence() {
+        let storage = MemoryStorage::default();
+        assert_eq!(
+            Keybindings::load(&storage).get_binding(Command::NextUnit),
+            Some(Binding::new(Key::Tab, false))
+        );
+
+        // the default key of an unbound command may be free again
+        let mut keybindings = Keybindings::default();
+        keybindings.bind(Command::NextTurn, Binding::new(Key::Tab, false));
+        keybindings.bind(Command::NextTurn, Binding::new(Key::N, false));
+        keybindings.save(&storage);
+
+        let loaded = Keybindings::load(&storage);
+        assert_eq!(loaded.get_binding(Command::NextUnit), None);
+        assert_eq!(loaded.get_command(Key::Tab, false), None);
+        assert_eq!(loaded.get_command(Key::N, false), Some(Command::NextTurn));
+    }
+
+    #[test]
+    fn serialization() {
+        let mut keybindings = Keybindings::default();
+        keybindings.bind(Command::SecondAttack, Binding::new(Key::Digit2, true));
+        keybindings.bind(Command::MoveCursor(2), Binding::new(Key::L, false));
+
+        let data = keybindings.serialize();
+        assert!(data.contains("Second attack=Shift+Digit2"));
+        let deserialized = Keybindings::deserialize(&data);
+        for command in Command::iter() {
+            assert_eq!(
+                deserialized.get_binding(command),
+                keybindings.get_binding(command)
+            );
+        }
+
+        let keybindings = Keybindings::deserialize("Next turn=N\ninvalid\nDeselect=Unknown");
+        assert_eq!(
+            keybindings.get_command(Key::N, false),
+            Some(Command::NextTurn)
+        );
+        assert_eq!(
+            keybindings.get_binding(Command::Deselect),
+            Some(Binding::new(Key::Escape, false))
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::viewport::Viewport;
+use wasm_game_lib::graphics::canvas::*;
+use wasm_game_lib::graphics::drawable::*;
+
+const LAYER_COUNT: usize = 5;
+
+// in drawing order
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Layer {
+    Terrain,
+    Highlights, // reachable hexes, path, targets and keyboard cursor
+    Units,
+    Telegraphs, // actions of the barbarians and consequences of the selected action
+    Ui,         // margin, combat log and tooltip
+}
+
+// what the layers are drawn from
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Input {
+    Viewport,  // camera, size of the screen and margin
+    Pointer,   // hover and pressed states, keyboard cursor
+    Selection, // selected unit and previsualisation
+    Board,     // units, their lives and their actions
+    Animation, // movements and flashes of the units
+    Blink,     // the blinking life losses, drawn from the clock
+    Texts,     // combat log, tooltip and translations
+}
+
+impl Layer {
+    // Fake iter over all the layers
+    pub fn iter() -> [Layer; LAYER_COUNT] {
+        [
+            Layer::Terrain,
+            Layer::Highlights,
+            Layer::Units,
+            Layer::Telegraphs,
+            Layer::Ui,
+        ]
+    }
+
+    pub fn depends_on(self, input: Input) -> bool {
+        match (self, input) {
+            (_, Input::Viewport) => true,
+            (Layer::Terrain, _) => false,
+            (Layer::Highlights, Input::Pointer) | (Layer::Highlights, Input::Selection) => true,
+            (Layer::Units, Input::Board) | (Layer::Units, Input::Animation) => true,
+            (Layer::Telegraphs, Input::Board)
+            | (Layer::Telegraphs, Input::Selection)
+            | (Layer::Telegraphs, Input::Blink) => true,
+            (Layer::Ui, input) => input != Input::Animation && input != Input::Blink,
+            _ => false,
+        }
+    }
+}
+
+// one offscreen canvas per layer, a layer is only drawn again after one of its inputs changed
+pub struct Compositor {
+    canvases: Vec<Canvas>, // in the order of Layer::iter()
+    dirty: [bool; LAYER_COUNT],
+    viewport: Option<Viewport>, // the layers were drawn with
+}
+
+impl Default for Compositor {
+    fn default() -> Compositor {
+        Compositor {
+            canvases: Layer::iter().iter().map(|_layer| Canvas::new()).collect(),
+            dirty: [true; LAYER_COUNT],
+            viewport: None,
+        }
+    }
+}
+
+impl Compositor {
+    pub fn invalidate(&mut self, input: Input) {
+        for (idx, layer) in Layer::iter().iter().enumerate() {
+            if layer.depends_on(input) {
+                self.dirty[idx] = true;
+            }
+        }
+    }
+
+    // the canvases are resized with the screen
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        if self.viewport == Some(viewport) {
+            return;
+        }
+        if self.viewport.map(|v| v.dimensions) != Some(viewport.dimensions) {
+            for canvas in self.canvases.iter_mut() {
+                canvas.set_width(viewport.dimensions.0);
+                canvas.set_height(viewport.dimensions.1);
+            }
+        }
+        self.viewport = Some(viewport);
+        self.invalidate(Input::Viewport);
+    }
+
+    // returns the cleared canvas of the layer if it must be drawn again
+    pub fn redraw(&mut self, layer: Layer) -> Option<&mut Canvas> {
+        let idx = layer as usize;
+        if !self.dirty[idx] {
+            return None;
+        }
+        self.dirty[idx] = false;
+        let canvas = &mut self.canvases[idx];
+        let element = canvas.get_canvas_element();
+        let (width, height) = (element.width() as f64, element.height() as f64);
+        canvas
+            .get_2d_canvas_rendering_context()
+            .clear_rect(0.0, 0.0, width, height);
+        Some(canvas)
+    }
+}
+
+impl Drawable for Compositor {
+    fn draw_on_canvas(&self, canvas: &mut Canvas) {
+        let context = canvas.get_2d_canvas_rendering_context();
+        for layer in &self.canvases {
+            context
+                .draw_image_with_html_canvas_element(layer.get_canvas_element(), 0.0, 0.0)
+                .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dependencies() {
+        for layer in Layer::iter().iter() {
+            assert!(layer.depends_on(Input::Viewport));
+        }
+        let dependents = |input: Input| -> Vec<Layer> {
+            Layer::iter()
+                .iter()
+                .copied()
+                .filter(|layer| layer.depends_on(input))
+                .collect()
+        };
+        // the units are not drawn again while a life loss blinks
+        assert_eq!(dependents(Input::Animation), vec![Layer::Units]);
+        assert_eq!(dependents(Input::Blink), vec![Layer::Telegraphs]);
+        assert!(!Layer::Terrain.depends_on(Input::Board));
+        assert!(Layer::Highlights.depends_on(Input::Pointer));
+        assert!(!Layer::Units.depends_on(Input::Pointer));
+        assert!(Layer::Telegraphs.depends_on(Input::Selection));
+        assert!(Layer::Ui.depends_on(Input::Texts));
+    }
+}
@@ -0,0 +1,196 @@
+// (x, y, width, height)
+pub type Rect = (f64, f64, f64, f64);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+// where the children are packed when they do not fill the stack
+// nothing is packed at the end yet
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Anchor {
+    Start,
+    Center,
+    End,
+}
+
+// length of a child along the axis of its stack
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Size {
+    Fixed(f64),
+    Ratio(f64), // of the length of the stack
+    Fill,       // shares the remaining length with the other fill children
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Item {
+    pub size: Size,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Item {
+    pub fn fixed(length: f64) -> Item {
+        Item::new(Size::Fixed(length))
+    }
+
+    pub fn ratio(ratio: f64) -> Item {
+        Item::new(Size::Ratio(ratio))
+    }
+
+    pub fn fill() -> Item {
+        Item::new(Size::Fill)
+    }
+
+    fn new(size: Size) -> Item {
+        Item {
+            size,
+            min: 0.0,
+            max: std::f64::INFINITY,
+        }
+    }
+
+    pub fn clamped(mut self, min: f64, max: f64) -> Item {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    fn clamp(&self, length: f64) -> f64 {
+        length.max(self.min).min(self.max)
+    }
+}
+
+// children are placed one after the other and stretched on the other axis
+pub struct Stack {
+    pub axis: Axis,
+    pub anchor: Anchor,
+    pub padding: f64, // around the children
+    pub spacing: f64, // between the children
+    pub items: Vec<Item>,
+}
+
+impl Stack {
+    pub fn new(axis: Axis, items: Vec<Item>) -> Stack {
+        Stack {
+            axis,
+            anchor: Anchor::Start,
+            padding: 0.0,
+            spacing: 0.0,
+            items,
+        }
+    }
+
+    // one rect per item, in the same order
+    pub fn layout(&self, area: Rect) -> Vec<Rect> {
+        let (x, y, width, height) = (
+            area.0 + self.padding,
+            area.1 + self.padding,
+            (area.2 - self.padding * 2.0).max(0.0),
+            (area.3 - self.padding * 2.0).max(0.0),
+        );
+        let (length, cross) = match self.axis {
+            Axis::Horizontal => (width, height),
+            Axis::Vertical => (height, width),
+        };
+        let spacing = self.spacing * self.items.len().saturating_sub(1) as f64;
+
+        let mut lengths: Vec<Option<f64>> = self
+            .items
+            .iter()
+            .map(|item| match item.size {
+                Size::Fixed(fixed) => Some(item.clamp(fixed)),
+                Size::Ratio(ratio) => Some(item.clamp(length * ratio)),
+                Size::Fill => None,
+            })
+            .collect();
+
+        // a fill child reaching its min or max gets fixed and the others share the rest
+        loop {
+            let used: f64 = lengths.iter().filter_map(|l| *l).sum();
+            let fill_count = lengths.iter().filter(|l| l.is_none()).count();
+            if fill_count == 0 {
+                break;
+            }
+            let share = (length - spacing - used).max(0.0) / fill_count as f64;
+            let clamped = self
+                .items
+                .iter()
+                .zip(lengths.iter())
+                .position(|(item, l)| l.is_none() && item.clamp(share) != share);
+            match clamped {
+                Some(idx) => lengths[idx] = Some(self.items[idx].clamp(share)),
+                None => {
+                    for l in lengths.iter_mut().filter(|l| l.is_none()) {
+                        *l = Some(share);
+                    }
+                }
+            }
+        }
+
+        let lengths: Vec<f64> = lengths.into_iter().map(|l| l.unwrap_or(0.0)).collect();
+        let total = lengths.iter().sum::<f64>() + spacing;
+        let mut position = match self.anchor {
+            Anchor::Start => 0.0,
+            Anchor::Center => ((length - total) / 2.0).max(0.0),
+            Anchor::End => (length - total).max(0.0),
+        };
+
+        let mut rects = Vec::new();
+        for item_length in lengths {
+            rects.push(match self.axis {
+                Axis::Horizontal => (x + position, y, item_length, cross),
+                Axis::Vertical => (x, y + position, cross, item_length),
+            });
+            position += item_length + self.spacing;
+        }
+        rects
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stacks() {
+        let mut stack = Stack::new(
+            Axis::Vertical,
+            vec![Item::fixed(50.0), Item::fill(), Item::fixed(100.0)],
+        );
+        stack.padding = 10.0;
+        stack.spacing = 10.0;
+        assert_eq!(
+            stack.layout((0.0, 0.0, 200.0, 500.0)),
+            vec![
+                (10.0, 10.0, 180.0, 50.0),
+                (10.0, 70.0, 180.0, 310.0),
+                (10.0, 390.0, 180.0, 100.0)
+            ]
+        );
+
+        // the fill child cannot be smaller than its min
+        stack.items[1] = Item::fill().clamped(400.0, 1000.0);
+        assert_eq!(stack.layout((0.0, 0.0, 200.0, 500.0))[2].1, 480.0);
+
+        let stack = Stack::new(
+            Axis::Horizontal,
+            vec![Item::ratio(0.2).clamped(150.0, 300.0), Item::fill()],
+        );
+        assert_eq!(
+            stack.layout((0.0, 0.0, 500.0, 100.0)),
+            vec![(0.0, 0.0, 150.0, 100.0), (150.0, 0.0, 350.0, 100.0)]
+        );
+        assert_eq!(stack.layout((0.0, 0.0, 2000.0, 100.0))[0].2, 300.0);
+
+        let mut stack = Stack::new(Axis::Horizontal, vec![Item::fixed(20.0), Item::fixed(40.0)]);
+        stack.spacing = 10.0;
+        stack.anchor = Anchor::End;
+        assert_eq!(stack.layout((0.0, 0.0, 100.0, 10.0))[0].0, 30.0);
+        stack.anchor = Anchor::Center;
+        assert_eq!(stack.layout((0.0, 0.0, 100.0, 10.0))[0].0, 15.0);
+    }
+}
@@ -5,42 +5,88 @@ macro_rules! log {
     }
 }
 
+use std::cell::RefCell;
 use std::convert::TryInto;
-use std::time::Duration;
 use wasm_bindgen::{prelude::*, JsCast};
 use wasm_game_lib::graphics::image::Image;
 use wasm_game_lib::graphics::sprite::Sprite;
-use wasm_game_lib::graphics::{canvas::*, color::*, font::*, window::*};
+use wasm_game_lib::graphics::{canvas::*, color::*, window::*};
 use wasm_game_lib::inputs::{event::types::*, mouse::*};
 use wasm_game_lib::inputs::{event::Event, keyboard::*, mouse::*};
-use wasm_game_lib::system::sleep;
 use web_sys;
 mod actions;
+mod animation;
+mod assets;
+mod atlas;
+mod audio;
+mod battle;
 mod button;
+mod camera;
+mod checkbox;
+mod combat_log;
+mod events;
+mod frame;
+mod i18n;
 mod idx;
+mod info_panel;
+mod input;
+mod keybindings;
+mod layers;
+mod layout;
 mod life;
 mod loader;
 mod map;
+mod menu;
+mod modal;
 mod pathfinder;
+mod pause;
+mod preferences;
 mod previsualisation;
 mod progress_bar;
 mod random;
+mod results;
+mod scenario;
+mod scene;
+mod scroll_panel;
+mod settings;
+mod slider;
+mod storage;
 mod textbox;
+mod tooltip;
+mod touch;
 mod units;
+mod viewport;
+mod widget;
+use assets::*;
+use atlas::*;
+use audio::*;
 use button::*;
+use camera::*;
+use combat_log::*;
+use events::*;
+use frame::*;
+use i18n::*;
 use idx::*;
-use loader::load_images;
+use input::*;
+use keybindings::*;
+use layout::*;
 use map::*;
 use pathfinder::*;
+use preferences::*;
+use scene::*;
+use storage::*;
 use textbox::*;
+use tooltip::*;
+use touch::*;
 use units::*;
+use viewport::*;
 
 pub struct DrawingData<'a> {
-    pub margin: usize,
-    pub dimensions: (u32, u32),
+    pub viewport: Viewport,
     pub position: &'a HexIndex,
     pub factor: f64,
-    pub animation_frame: u64,
+    pub clock: f64,             // in milliseconds
+    pub offset: (isize, isize), // used to animate units
 }
 
 #[allow(clippy::single_match)]
@@ -51,131 +97,65 @@ pub async fn start() -> Result<(), JsValue> {
 
     let (mut window, mut canvas) =
         Window::init_with_events(KEYBOARD_EVENT + RESIZE_EVENT + MOUSE_EVENT);
-    let t = load_images(
-        vec![
-            "textures/plains/grassy_plain1", // 0
-            "textures/plains/grassy_plain2",
-            "textures/plains/grassy_plain3",
-            "textures/plains/grassy_plain4",
-            "textures/forest/forest1",
-            "textures/forest/forest2", // 5
-            "textures/forest/forest3",
-            "textures/forest/forest4",
-            "textures/plains/plain1",
-            "textures/plains/plain2",
-            "textures/plains/plain3", // 10
-            "textures/plains/plain4",
-            "textures/underground/dirt",
-            "textures/shadow.png",
-            "textures/red.png",
-            "units/archer.png", // 15
-            "units/knight.png",
-            "units/scout.png",
-            "units/barbarian.png",
-            "units/barbarian2.png",
-            "units/barbarian3.png", // 20
-            "units/barbarian4.png",
-        ],
-        &mut canvas,
-    )
-    .await;
-
-    let mut margin = canvas.get_width() as usize / 5;
-    let mut arrows = (false, false, false, false);
-    let (mut width, mut height) = (window.get_width(), window.get_height());
-    let mut map = Map::new(
-        [
-            &t[0], &t[1], &t[2], &t[3], &t[4], &t[5], &t[6], &t[7], &t[8], &t[9], &t[10], &t[11],
-            &t[12], &t[13], &t[14], &t[15], &t[16],
-        ],
-        (width as usize, height as usize),
-        margin,
-    );
-    let arial = Font::arial();
-    let mut units = Units::new(
-        [&t[15], &t[16], &t[17], &t[18], &t[19], &t[20], &t[21]],
-        [&t[13], &t[14]],
-        margin,
-        &arial,
-    );
+    let storage = LocalStorage;
+    let preferences = RefCell::new(Preferences::load(&storage));
+    if let Some(locale) = preferences
+        .borrow()
+        .locale
+        .clone()
+        .or_else(get_browser_locale)
+    {
+        set_locale(&locale);
+    }
+    let assets = Assets::load(&mut canvas).await?;
+    let textures = &assets.textures;
 
-    units.set(&3.try_into().unwrap(), Some(Unit::new(UnitType::Archer)));
-    units.set(&4.try_into().unwrap(), Some(Unit::new(UnitType::Scout)));
-    units.set(&5.try_into().unwrap(), Some(Unit::new(UnitType::Knight)));
-    units.set(&6.try_into().unwrap(), Some(Unit::new(UnitType::Barbarian)));
-    units.set(
-        &35.try_into().unwrap(),
-        Some(Unit::new(UnitType::BarbarianVariant)),
-    );
-    units.set(
-        &51.try_into().unwrap(),
-        Some(Unit::new(UnitType::ArmoredBarbarian)),
-    );
-    units.set(
-        &42.try_into().unwrap(),
-        Some(Unit::new(UnitType::BarbarianLordOfDeath)),
-    );
+    let viewport = RefCell::new(Viewport::new((canvas.get_width(), canvas.get_height())));
+    let arial = assets.fonts.get(Category::Font, "arial");
+    let scenarios = assets.scenarios.get(Category::Scenario, "scenarios");
+    let icons = Atlas::new_icons(textures.get(Category::Atlas, "icons"));
+    let audio = RefCell::new(AudioManager::new(HtmlBackend::new(&assets.sounds)));
+    let touch_listener = TouchListener::new(canvas.get_canvas_element());
+    let context = Context {
+        textures,
+        icons: &icons,
+        arial,
+        scenarios,
+        viewport: &viewport,
+        audio: &audio,
+        preferences: &preferences,
+        storage: &storage,
+    };
+    context.apply_preferences();
+    let mut scenes = SceneStack::new(context);
+    let mut clock = FrameClock::default();
 
     loop {
+        let now = next_frame().await;
         for event in window.poll_events() {
             match event {
-                Event::KeyboardEvent(ke) => match ke {
-                    KeyboardEvent::Down(key) => match key {
-                        Key::UpArrow => arrows.0 = true,
-                        Key::RightArrow => arrows.1 = true,
-                        Key::DownArrow => arrows.2 = true,
-                        Key::LeftArrow => arrows.3 = true,
-                        _ => (),
-                    },
-                    KeyboardEvent::Up(key) => match key {
-                        Key::UpArrow => arrows.0 = false,
-                        Key::RightArrow => arrows.1 = false,
-                        Key::DownArrow => arrows.2 = false,
-                        Key::LeftArrow => arrows.3 = false,
-                        _ => (),
-                    },
-                },
-                Event::ResizeEvent(w, h) => {
-                    width = w;
-                    height = h;
+                Event::ResizeEvent(width, height) => {
                     canvas.set_width(width);
                     canvas.set_height(height);
-                    margin = canvas.get_width() as usize / 5;
-                    map.margin = margin;
-                    units.handle_resize_event(&mut canvas);
-                    units.set_margin(margin);
-                    map.dimensions = (width as usize, height as usize)
+                    viewport.borrow_mut().resize((width, height));
                 }
-                Event::MouseEvent(me) => match me {
-                    MouseEvent::Move(x, y) => {
-                        units.handle_mouse_move(&map, x, y);
-                    }
-                    MouseEvent::Click(x, y) => {
-                        units.handle_mouse_click(&mut map, x, y, &arial, &mut canvas);
-                    }
-                    _ => (),
-                },
-                event => log!("{:?}", event),
+                event => scenes.handle_event(event),
             }
         }
-
-        if arrows.0 {
-            map.coords.1 += 3;
-        }
-        if arrows.1 {
-            map.coords.0 -= 3;
+        for touch in touch_listener.poll_inputs() {
+            scenes.handle_touch(touch);
         }
-        if arrows.2 {
-            map.coords.1 -= 3;
+
+        // nothing is updated nor drawn while the tab is hidden
+        if is_hidden() {
+            clock.pause();
+            continue;
         }
-        if arrows.3 {
-            map.coords.0 += 3;
+        for _step in 0..clock.advance(now) {
+            scenes.update(FIXED_STEP);
         }
 
         canvas.clear_with_black();
-        canvas.draw(&map);
-        canvas.draw(&units);
-
-        sleep(Duration::from_millis(16)).await;
+        scenes.draw(&mut canvas);
     }
 }
@@ -13,7 +13,7 @@ const BORDER_STYLE: LineStyle = LineStyle {
     },
 };
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub struct Life {
     max: usize,
     current: usize,
@@ -73,6 +73,14 @@ impl Life {
         }
     }
 
+    pub fn get_current(&self) -> usize {
+        self.current
+    }
+
+    pub fn get_max(&self) -> usize {
+        self.max
+    }
+
     pub fn is_dead(&self) -> bool {
         self.current == 0
     }
@@ -102,11 +110,9 @@ impl Life {
         let point_width = (width - 4.0) / self.max as f64;
 
         let coords = data.position.get_canvas_coords();
-        let coords = Map::internal_coords_to_screen_coords(
-            data.dimensions,
-            data.margin,
-            coords.0 as isize + (256 - width as isize) / 2,
-            coords.1 as isize + 300,
+        let coords = data.viewport.board_to_screen(
+            coords.0 as isize + (256 - width as isize) / 2 + data.offset.0,
+            coords.1 as isize + 300 + data.offset.1,
         );
         let context = canvas.get_2d_canvas_rendering_context();
         context.begin_path();
@@ -129,7 +135,7 @@ impl Life {
             if i as isize >= self.current as isize - self.loss {
                 context.set_fill_style(&JsValue::from_str(&format!(
                     "rgba(255, 0, 0, {})",
-                    (data.animation_frame as f64 % 75.0) / 75.0
+                    (data.clock % 1250.0) / 1250.0
                 )));
             }
             context.fill_rect(
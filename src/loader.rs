@@ -1,77 +1,298 @@
-use crate::progress_bar::ProgressBar;
+use crate::{i18n::*, progress_bar::ProgressBar, scenario::Scenario};
 use futures::{
-    channel::{oneshot, oneshot::Receiver},
-    future::join_all,
+    channel::{oneshot, oneshot::Receiver, oneshot::Sender},
+    future::{join_all, select, Either},
     join,
 };
+use js_sys::Promise;
 use std::time::Duration;
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
 use wasm_game_lib::{
     graphics::{canvas::*, color::*, font::*, image::*, text::*},
     system::sleep,
 };
+use web_sys::{HtmlAudioElement, Response};
 
-pub async fn load_images(images: Vec<&str>, mut canvas: &mut Canvas) -> Vec<Image> {
-    let mut receivers = Vec::new();
-    let mut senders = Vec::new();
-    for _i in 0..images.len() {
-        let (sender, receiver) = oneshot::channel::<Result<Image, JsValue>>();
-        receivers.push(receiver);
-        senders.push(sender);
+const ATTEMPTS: usize = 3;
+const RETRY_DELAY: u64 = 500; // in milliseconds
+const REPORT_DURATION: u64 = 3000; // in milliseconds
+const SOUND_TIMEOUT: u64 = 5000; // in milliseconds
+
+// magenta and black checkerboard replacing the optional images that cannot be loaded
+const PLACEHOLDER: &str =
+    "data:image/svg+xml,<svg xmlns='http://www.w3.org/2000/svg' width='256' height='384'>\
+<rect width='256' height='384' fill='magenta'/>\
+<rect width='128' height='192' fill='black'/>\
+<rect x='128' y='192' width='128' height='192' fill='black'/></svg>";
+
+// how a file is decoded
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Kind {
+    Image,
+    Font,
+    Sound,
+    Scenarios, // text file decoded into scenarios
+}
+
+pub enum Loaded {
+    Image(Image),
+    Font(Font),
+    Sound(HtmlAudioElement),
+    Scenarios(Vec<Scenario>),
+}
+
+// a file to load, the weight is its share of the progress bar
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LoadRequest<'a> {
+    pub path: &'a str,
+    pub kind: Kind,
+    pub weight: usize,
+    pub required: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Loaded,
+    Replaced, // by the placeholder of its kind
+    Failed,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FailedAsset {
+    pub path: String,
+    pub replaced: bool,
+}
+
+// assets that could not be loaded, displayed on the loading screen
+#[derive(Default, PartialEq, Debug)]
+pub struct LoadReport {
+    pub failed: Vec<FailedAsset>,
+}
+
+impl LoadReport {
+    pub fn new(paths: &[&str], outcomes: &[Outcome]) -> LoadReport {
+        let failed = paths
+            .iter()
+            .zip(outcomes.iter())
+            .filter(|(_path, outcome)| **outcome != Outcome::Loaded)
+            .map(|(path, outcome)| FailedAsset {
+                path: path.to_string(),
+                replaced: *outcome == Outcome::Replaced,
+            })
+            .collect();
+        LoadReport { failed }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    // a required asset is missing so the game cannot start
+    pub fn is_fatal(&self) -> bool {
+        self.failed.iter().any(|asset| !asset.replaced)
+    }
+
+    pub fn get_lines(&self) -> Vec<String> {
+        let mut lines = vec![String::from(if self.is_fatal() {
+            tr("loading.failed")
+        } else {
+            tr("loading.replaced")
+        })];
+        for asset in &self.failed {
+            let key = if asset.replaced {
+                "loading.placeholder"
+            } else {
+                "loading.missing"
+            };
+            lines.push(tr_args(key, &[&asset.path]));
+        }
+        lines
     }
+}
 
+// optional assets that cannot be loaded are replaced by a placeholder
+pub async fn load_assets(
+    requests: Vec<LoadRequest<'_>>,
+    mut canvas: &mut Canvas,
+) -> Result<Vec<Loaded>, JsValue> {
+    let mut receivers = Vec::new();
     let mut futures = Vec::new();
-    for image in images {
-        futures.push(Image::load_and_send(image, senders.remove(0)))
+    for request in &requests {
+        let (sender, receiver) = oneshot::channel::<(Outcome, Option<Loaded>)>();
+        receivers.push((receiver, request.weight));
+        futures.push(load_with_retries(*request, sender));
     }
 
     let results = join!(loading_tracker(receivers, &mut canvas), join_all(futures)).0;
-    let mut images = Vec::new();
-    for result in results {
-        images.push(result.expect("failed to load an image"));
+    let paths: Vec<&str> = requests.iter().map(|request| request.path).collect();
+    let outcomes: Vec<Outcome> = results.iter().map(|(outcome, _asset)| *outcome).collect();
+    let report = LoadReport::new(&paths, &outcomes);
+
+    if !report.is_empty() {
+        for line in report.get_lines() {
+            log!("{}", line);
+        }
+        draw_report(&report, &mut canvas);
+        if report.is_fatal() {
+            return Err(JsValue::from_str("failed to load required ressources"));
+        }
+        sleep(Duration::from_millis(REPORT_DURATION)).await;
+    } else {
+        log!("ressources loaded sucessfully");
+    }
+
+    Ok(results
+        .into_iter()
+        .filter_map(|(_outcome, asset)| asset)
+        .collect())
+}
+
+async fn load(path: &str, kind: Kind) -> Result<Loaded, JsValue> {
+    match kind {
+        Kind::Image => {
+            let (sender, receiver) = oneshot::channel::<Result<Image, JsValue>>();
+            Image::load_and_send(path, sender).await;
+            receiver
+                .await
+                .unwrap_or_else(|_canceled| Err(JsValue::NULL))
+                .map(Loaded::Image)
+        }
+        Kind::Font => Font::load(path).await.map(Loaded::Font),
+        Kind::Sound => load_sound(path).await.map(Loaded::Sound),
+        Kind::Scenarios => load_text(path)
+            .await
+            .map(|text| Loaded::Scenarios(Scenario::load(&text))),
+    }
+}
+
+async fn load_placeholder(kind: Kind) -> Result<Loaded, JsValue> {
+    match kind {
+        Kind::Image => load(PLACEHOLDER, Kind::Image).await,
+        Kind::Font => Ok(Loaded::Font(Font::arial())),
+        Kind::Sound => HtmlAudioElement::new().map(Loaded::Sound),
+        Kind::Scenarios => Ok(Loaded::Scenarios(Scenario::get_builtin())),
+    }
+}
+
+// resolves when the browser can play the whole sound without buffering
+// some browsers never preload before a user gesture, the attempt fails after a timeout
+async fn load_sound(path: &str) -> Result<HtmlAudioElement, JsValue> {
+    let audio = HtmlAudioElement::new_with_src(path)?;
+    let promise = Promise::new(&mut |resolve, reject| {
+        audio.set_oncanplaythrough(Some(&resolve));
+        audio.set_onerror(Some(&reject));
+    });
+    let timeout = Box::pin(sleep(Duration::from_millis(SOUND_TIMEOUT)));
+    let result = match select(JsFuture::from(promise), timeout).await {
+        Either::Left((result, _timeout)) => result,
+        Either::Right((_elapsed, _loading)) => Err(JsValue::from_str("timed out")),
+    };
+    audio.set_oncanplaythrough(None);
+    audio.set_onerror(None);
+    result.map(|_event| audio)
+}
+
+async fn load_text(path: &str) -> Result<String, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let response: Response = JsFuture::from(window.fetch_with_str(path))
+        .await?
+        .dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("status {}", response.status())));
+    }
+    JsFuture::from(response.text()?)
+        .await?
+        .as_string()
+        .ok_or_else(|| JsValue::from_str("not a text file"))
+}
+
+async fn load_with_retries(request: LoadRequest<'_>, sender: Sender<(Outcome, Option<Loaded>)>) {
+    for attempt in 1..=ATTEMPTS {
+        if let Ok(asset) = load(request.path, request.kind).await {
+            let _ = sender.send((Outcome::Loaded, Some(asset)));
+            return;
+        }
+        log!(
+            "failed to load {} (attempt {}/{})",
+            request.path,
+            attempt,
+            ATTEMPTS
+        );
+        if attempt < ATTEMPTS {
+            sleep(Duration::from_millis(RETRY_DELAY)).await;
+        }
     }
 
-    log!("ressources loaded sucessfully");
+    if !request.required {
+        if let Ok(asset) = load_placeholder(request.kind).await {
+            let _ = sender.send((Outcome::Replaced, Some(asset)));
+            return;
+        }
+    }
+    let _ = sender.send((Outcome::Failed, None));
+}
 
-    images
+fn draw_report(report: &LoadReport, canvas: &mut Canvas) {
+    let arial = Font::arial();
+    canvas.clear();
+    for (idx, line) in report.get_lines().into_iter().enumerate() {
+        canvas.draw(&Text::new_with_options(
+            &arial,
+            line,
+            (100, 100 + idx * 30),
+            TextStyle::default(),
+            (if idx == 0 { 30 } else { 20 }, "px"),
+        ));
+    }
 }
 
+// receivers: (receiver, weight) of every asset
 async fn loading_tracker(
-    mut receivers: Vec<Receiver<Result<Image, JsValue>>>,
+    mut receivers: Vec<(Receiver<(Outcome, Option<Loaded>)>, usize)>,
     canvas: &mut Canvas,
-) -> Vec<Result<Image, JsValue>> {
-    let mut images = Vec::new();
+) -> Vec<(Outcome, Option<Loaded>)> {
+    let mut assets = Vec::new();
     for _ in 0..receivers.len() {
-        images.push(None);
+        assets.push(None);
     }
 
     let arial = Font::arial();
     let message = Text::new_with_options(
         &arial,
-        String::from("Loading ressources... Please wait"),
+        tr("loading.message").to_string(),
         (100, 200),
         TextStyle::default(),
         (30, "px"),
     );
 
-    let mut progress_bar = ProgressBar::new(receivers.len(), (100.0, 100.0), (300.0, 50.0));
+    let total_weight = receivers.iter().map(|(_receiver, weight)| weight).sum();
+    let mut progress_bar = ProgressBar::new(total_weight, (100.0, 100.0), (300.0, 50.0));
     progress_bar.style.color = Color::grey();
     progress_bar.border_radius = 5.0;
     progress_bar.style.size = 2.0;
     progress_bar.background_color = Color::new(229, 229, 229);
 
     loop {
-        for i in 0..images.len() {
-            if images[i].is_none() {
-                if let Ok(Some(result)) = receivers[i].try_recv() {
-                    progress_bar.inc();
-                    images[i] = Some(result);
+        for (i, (receiver, weight)) in receivers.iter_mut().enumerate() {
+            if assets[i].is_none() {
+                match receiver.try_recv() {
+                    Ok(Some(result)) => {
+                        progress_bar.add(*weight);
+                        assets[i] = Some(result);
+                    }
+                    // the loading future was dropped without answering
+                    Err(_canceled) => {
+                        progress_bar.add(*weight);
+                        assets[i] = Some((Outcome::Failed, None));
+                    }
+                    Ok(None) => (),
                 }
             }
         }
 
-        if !images.contains(&None) {
-            // break when every image is ready
+        if assets.iter().all(|asset| asset.is_some()) {
+            // break when every asset is ready
             break;
         }
 
@@ -82,10 +303,39 @@ async fn loading_tracker(
         sleep(Duration::from_millis(16)).await;
     }
 
-    let mut unwraped_images = Vec::new();
-    for image in images {
-        unwraped_images.push(image.unwrap());
-    }
+    assets.into_iter().map(|asset| asset.unwrap()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    unwraped_images
+    #[test]
+    fn report() {
+        let paths = ["a.png", "b.png", "c.png"];
+        let report = LoadReport::new(&paths, &[Outcome::Loaded; 3]);
+        assert!(report.is_empty());
+        assert!(!report.is_fatal());
+
+        let report = LoadReport::new(
+            &paths,
+            &[Outcome::Replaced, Outcome::Loaded, Outcome::Loaded],
+        );
+        assert!(!report.is_fatal());
+        assert_eq!(
+            report.get_lines(),
+            vec![
+                "Some ressources are missing and have been replaced:",
+                "a.png (placeholder)"
+            ]
+        );
+
+        let report = LoadReport::new(
+            &paths,
+            &[Outcome::Replaced, Outcome::Loaded, Outcome::Failed],
+        );
+        assert!(report.is_fatal());
+        assert_eq!(report.failed.len(), 2);
+        assert_eq!(report.get_lines()[2], "c.png (missing)");
+    }
 }
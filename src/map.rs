@@ -1,5 +1,9 @@
-use crate::{idx::HexIndex, random::get_random, units::*};
+use crate::{
+    actions::PushPhysics, assets::*, i18n::tr, idx::HexIndex, random::get_random,
+    scenario::Scenario, units::*, viewport::*,
+};
 use arr_macro::arr;
+use std::cell::RefCell;
 use wasm_game_lib::graphics::{canvas::Canvas, color::*, drawable::*, image::*};
 
 fn idx_to_y(idx: usize) -> usize {
@@ -50,7 +54,6 @@ fn idx_to_coords(idx: usize) -> (usize, usize) {
     (idx_to_x(idx), idx_to_y(idx))
 }
 
-const TEXTURES_NUMBER: usize = 17;
 pub const CANVAS_WIDTH: f64 = 9.0 * 253.0;
 pub const CANVAS_HEIGHT: f64 = 8.0 * 256.0 + 10.0;
 
@@ -59,38 +62,65 @@ pub enum Tile {
     GrassyPlain(u8),
     Forest(u8),
     Plain(u8),
+    Rocks(u8), // the hazard of the push rules
 }
 
 impl Tile {
-    fn get_texture_idx(self) -> u8 {
+    // (name, variant) of the texture in the registry
+    fn get_texture_name(self) -> (&'static str, usize) {
         match self {
-            Tile::GrassyPlain(number) => number,
-            Tile::Forest(number) => 4 + number,
-            Tile::Plain(number) => 8 + number,
+            Tile::GrassyPlain(number) => ("grassy_plain", number as usize),
+            Tile::Forest(number) => ("forest", number as usize),
+            Tile::Plain(number) => ("plain", number as usize),
+            Tile::Rocks(number) => ("rocks", number as usize),
+        }
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Tile::GrassyPlain(_) => tr("tile.grassy_plain"),
+            Tile::Forest(_) => tr("tile.forest"),
+            Tile::Plain(_) => tr("tile.plain"),
+            Tile::Rocks(_) => tr("tile.rocks"),
+        }
+    }
+
+    pub fn get_rules(self) -> Vec<&'static str> {
+        let mut rules = vec![tr("tile.rule.cost")];
+        if self.is_hazard() {
+            rules.push(tr("tile.rule.hazard"));
+        }
+        rules
+    }
+
+    pub fn is_hazard(self) -> bool {
+        match self {
+            Tile::Rocks(_) => true,
+            Tile::GrassyPlain(_) | Tile::Forest(_) | Tile::Plain(_) => false,
         }
     }
 }
 
 pub struct Map<'a> {
-    pub coords: (isize, isize),
     tiles: [Tile; 61],
-    textures: [&'a Image; TEXTURES_NUMBER],
+    textures: &'a TextureRegistry,
     canvas: Canvas,
-    pub dimensions: (usize, usize),
-    pub margin: usize,
+    viewport: &'a RefCell<Viewport>,
+    pub push_physics: PushPhysics,
 }
 
 impl<'a> Map<'a> {
+    // the rocks and the push rules come from the scenario
     #[allow(clippy::cognitive_complexity)]
     pub fn new(
-        textures: [&'a Image; TEXTURES_NUMBER],
-        dimensions: (usize, usize),
-        margin: usize,
-    ) -> Map {
+        textures: &'a TextureRegistry,
+        viewport: &'a RefCell<Viewport>,
+        scenario: &Scenario,
+    ) -> Map<'a> {
         let mut canvas = Canvas::new();
         canvas.set_width(CANVAS_WIDTH as u32);
         canvas.set_height(CANVAS_HEIGHT as u32);
-        let tiles = arr!((
+        let mut tiles = arr!((
             {
                 let random = get_random(2);
                 match random {
@@ -99,14 +129,16 @@ impl<'a> Map<'a> {
                     _ => Tile::Plain(get_random(3)),
                 }
             }); 61);
+        for position in &scenario.rocks {
+            tiles[*position] = Tile::Rocks(get_random(3));
+        }
 
         let mut map = Map {
-            coords: (0, 0),
             tiles,
             textures,
             canvas,
-            dimensions,
-            margin,
+            viewport,
+            push_physics: scenario.push_physics.clone(),
         };
 
         map.update_canvas();
@@ -116,6 +148,7 @@ impl<'a> Map<'a> {
 
     pub fn update_canvas(&mut self) {
         for (idx, tile) in self.tiles.iter().enumerate() {
+            let (name, variant) = tile.get_texture_name();
             let coords = idx_to_coords(idx);
             let screen_coords = (coords.0 * 253, coords.1 * 193);
             let offset = match coords.1 {
@@ -129,7 +162,7 @@ impl<'a> Map<'a> {
 
             self.canvas.draw_image(
                 ((offset + screen_coords.0) as f64, screen_coords.1 as f64),
-                self.textures[tile.get_texture_idx() as usize],
+                self.textures.get_variant(Category::Tile, name, variant),
             );
 
             if coords.1 == 8
@@ -141,82 +174,35 @@ impl<'a> Map<'a> {
                         (offset + screen_coords.0) as f64,
                         screen_coords.1 as f64 + 318.45,
                     ),
-                    self.textures[12],
+                    self.textures.get(Category::Underground, "dirt"),
                 );
             }
         }
     }
-
-    pub fn screen_coords_to_internal_canvas_coords(&self, x: usize, y: usize) -> (isize, isize) {
-        let x = x as isize - self.margin as isize;
-        let factor_width: f64 = (self.dimensions.0 - self.margin) as f64 / CANVAS_WIDTH;
-        let factor_height = self.dimensions.1 as f64 / CANVAS_HEIGHT;
-        let smaller_factor = if factor_width < factor_height {
-            factor_width
-        } else {
-            factor_height
-        };
-        let fitting_width = CANVAS_WIDTH * smaller_factor;
-        let fitting_height = CANVAS_HEIGHT * smaller_factor;
-        let remaining_width = (self.dimensions.0 - self.margin) as f64 - fitting_width;
-        let remaining_height = self.dimensions.1 as f64 - fitting_height;
-        (
-            ((x as f64 - remaining_width / 2.0) / smaller_factor) as isize,
-            ((y as f64 - remaining_height / 2.0) / smaller_factor) as isize,
-        )
-    }
-
-    pub fn internal_coords_to_screen_coords(
-        dimensions: (u32, u32),
-        margin: usize,
-        x: isize,
-        y: isize,
-    ) -> (usize, usize) {
-        let factor_width: f64 = (dimensions.0 as usize - margin) as f64 / CANVAS_WIDTH;
-        let factor_height = dimensions.1 as f64 / CANVAS_HEIGHT;
-        let smaller_factor = if factor_width < factor_height {
-            factor_width
-        } else {
-            factor_height
-        };
-        let fitting_width = CANVAS_WIDTH * smaller_factor;
-        let fitting_height = CANVAS_HEIGHT * smaller_factor;
-        let remaining_width = (dimensions.0 as usize - margin) as f64 - fitting_width;
-        let remaining_height = dimensions.1 as f64 - fitting_height;
-
-        let mut x = x as f64 * smaller_factor;
-        x += remaining_width / 2.0;
-        let mut y = y as f64 * smaller_factor;
-        y += remaining_height / 2.0;
-
-        (x as usize + margin, y as usize)
-    }
 }
 
 impl<'a> Drawable for Map<'a> {
     fn draw_on_canvas(&self, canvas: &mut Canvas) {
-        let factor_width: f64 = (self.dimensions.0 - self.margin) as f64 / CANVAS_WIDTH;
-        let factor_height = self.dimensions.1 as f64 / CANVAS_HEIGHT;
-        let smaller_factor = if factor_width < factor_height {
-            factor_width
-        } else {
-            factor_height
-        };
-        let fitting_width = CANVAS_WIDTH * smaller_factor;
-        let fitting_height = CANVAS_HEIGHT * smaller_factor;
-        let remaining_width = (self.dimensions.0 - self.margin) as f64 - fitting_width;
-        let remaining_height = self.dimensions.1 as f64 - fitting_height;
+        let viewport = *self.viewport.borrow();
+        let (x, y) = viewport.board_to_screen(0, 0);
+        let factor = viewport.get_factor();
+        let area = viewport.get_board_area();
 
         let canvas_element = canvas.get_2d_canvas_rendering_context();
+        canvas_element.save();
+        canvas_element.begin_path();
+        canvas_element.rect(area.0, area.1, area.2, area.3);
+        canvas_element.clip();
         canvas_element
             .draw_image_with_html_canvas_element_and_dw_and_dh(
                 self.canvas.get_canvas_element(),
-                self.coords.0 as f64 + remaining_width / 2.0 + self.margin as f64,
-                self.coords.1 as f64 + remaining_height / 2.0,
-                fitting_width,
-                fitting_height,
+                x as f64,
+                y as f64,
+                CANVAS_WIDTH * factor,
+                CANVAS_HEIGHT * factor,
             )
             .unwrap();
+        canvas_element.restore();
     }
 }
 
@@ -234,7 +220,7 @@ impl<'a> std::ops::IndexMut<&HexIndex> for Map<'a> {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum Direction {
     TopLeft,
     TopRight,
@@ -256,6 +242,17 @@ impl Direction {
             Direction::Left,
         ]
     }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Direction::TopLeft => tr("direction.top_left"),
+            Direction::TopRight => tr("direction.top_right"),
+            Direction::Right => tr("direction.right"),
+            Direction::BottomRight => tr("direction.bottom_right"),
+            Direction::BottomLeft => tr("direction.bottom_left"),
+            Direction::Left => tr("direction.left"),
+        }
+    }
 }
 
 impl std::ops::Not for Direction {
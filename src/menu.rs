@@ -0,0 +1,152 @@
+use crate::{
+    battle::Battle, i18n::*, input::*, modal::Modal, scene::*, settings::Settings,
+    touch::TouchInput, widget::Widget,
+};
+use wasm_game_lib::graphics::canvas::*;
+use wasm_game_lib::inputs::event::Event;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Item {
+    Continue,
+    NewGame,
+    Scenarios,
+    Settings,
+}
+
+pub struct MainMenu<'a> {
+    modal: Modal<'a>,
+    input: InputHandler<'a>,
+    items: Vec<Item>, // in the order of the buttons
+}
+
+impl<'a> MainMenu<'a> {
+    // can_continue: a battle was left with the pause menu
+    pub fn new(context: &Context<'a>, can_continue: bool) -> MainMenu<'a> {
+        let mut items = vec![Item::NewGame, Item::Scenarios, Item::Settings];
+        if can_continue {
+            items.insert(0, Item::Continue);
+        }
+        let buttons = items
+            .iter()
+            .map(|item| match item {
+                Item::Continue => tr("menu.continue"),
+                Item::NewGame => tr("menu.new_game"),
+                Item::Scenarios => tr("menu.scenarios"),
+                Item::Settings => tr("menu.settings"),
+            })
+            .map(String::from)
+            .collect();
+
+        MainMenu {
+            modal: Modal::new(
+                context.arial,
+                tr("menu.title").to_string(),
+                Vec::new(),
+                buttons,
+            ),
+            input: InputHandler::new(context.viewport, context.preferences),
+            items,
+        }
+    }
+}
+
+impl<'a> Scene<'a> for MainMenu<'a> {
+    fn handle_event(&mut self, event: Event) {
+        self.input.handle_event(event, &mut self.modal);
+    }
+
+    fn handle_touch(&mut self, touch: TouchInput) {
+        self.input.handle_touch(touch, &mut self.modal);
+    }
+
+    fn update(&mut self, _delta: f64, context: &Context<'a>) -> Transition<'a> {
+        self.input.poll_intents();
+        match self.modal.take_choice().map(|idx| self.items[idx]) {
+            Some(Item::Continue) => Transition::Resume,
+            Some(Item::NewGame) => Transition::Reset(Box::new(Battle::new(context, 0))),
+            Some(Item::Scenarios) => Transition::Push(Box::new(ScenarioMenu::new(context))),
+            Some(Item::Settings) => Transition::Push(Box::new(Settings::new(context))),
+            None => Transition::None,
+        }
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let screen = (
+            0.0,
+            0.0,
+            canvas.get_width() as f64,
+            canvas.get_height() as f64,
+        );
+        self.modal.set_area(screen);
+        canvas.draw(&self.modal);
+    }
+
+    fn translate(&mut self, context: &Context<'a>) {
+        *self = MainMenu::new(context, self.items.contains(&Item::Continue));
+    }
+}
+
+// one button per scenario, the last one goes back to the main menu
+pub struct ScenarioMenu<'a> {
+    modal: Modal<'a>,
+    input: InputHandler<'a>,
+}
+
+impl<'a> ScenarioMenu<'a> {
+    pub fn new(context: &Context<'a>) -> ScenarioMenu<'a> {
+        let mut buttons: Vec<String> = context
+            .scenarios
+            .iter()
+            .map(|scenario| tr(&scenario.name).to_string())
+            .collect();
+        buttons.push(tr("menu.back").to_string());
+
+        ScenarioMenu {
+            modal: Modal::new(
+                context.arial,
+                tr("menu.scenarios").to_string(),
+                Vec::new(),
+                buttons,
+            ),
+            input: InputHandler::new(context.viewport, context.preferences),
+        }
+    }
+}
+
+impl<'a> Scene<'a> for ScenarioMenu<'a> {
+    fn handle_event(&mut self, event: Event) {
+        self.input.handle_event(event, &mut self.modal);
+    }
+
+    fn handle_touch(&mut self, touch: TouchInput) {
+        self.input.handle_touch(touch, &mut self.modal);
+    }
+
+    fn update(&mut self, _delta: f64, context: &Context<'a>) -> Transition<'a> {
+        if self.input.poll_intents().contains(&Intent::Cancel) {
+            return Transition::Pop;
+        }
+        match self.modal.take_choice() {
+            Some(idx) if idx < context.scenarios.len() => {
+                Transition::Reset(Box::new(Battle::new(context, idx)))
+            }
+            Some(_back) => Transition::Pop,
+            None => Transition::None,
+        }
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let screen = (
+            0.0,
+            0.0,
+            canvas.get_width() as f64,
+            canvas.get_height() as f64,
+        );
+        self.modal.set_area(screen);
+        canvas.draw(&self.modal);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
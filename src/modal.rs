@@ -0,0 +1,199 @@
+use crate::{button::*, idx::HexIndex, input::*, layout::*, widget::*};
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::canvas::*;
+use wasm_game_lib::graphics::color::*;
+use wasm_game_lib::graphics::drawable::*;
+use wasm_game_lib::graphics::font::*;
+use wasm_game_lib::graphics::text::*;
+
+const BOX_STYLE: LineStyle = LineStyle {
+    cap: LineCap::Round,
+    join: LineJoin::Round,
+    size: 4.0,
+    color: Color {
+        alpha: 255,
+        red: 67,
+        green: 75,
+        blue: 96,
+    },
+};
+
+const BOX_WIDTH: f64 = 500.0;
+const TITLE_HEIGHT: f64 = 60.0;
+const FONT_SIZE: usize = 20;
+const LINE_HEIGHT: f64 = 24.0;
+const PADDING: f64 = 20.0;
+const SPACING: f64 = 10.0;
+
+// a box centered on a dimmed screen, nothing below it gets the events while it is open
+// the message lines and the content are displayed below the title and above the buttons
+pub struct Modal<'a> {
+    screen: Rect,
+    area: Rect, // of the box
+    title: Text<'a>,
+    lines: Vec<Text<'a>>,
+    content: Rect, // space left to the widgets of the owner
+    buttons: Vec<Button<'a>>,
+    choice: Option<usize>, // index of the last clicked button
+    state: WidgetState,
+}
+
+impl<'a> Modal<'a> {
+    pub fn new(
+        font: &'a Font,
+        title: String,
+        lines: Vec<String>,
+        buttons: Vec<String>,
+    ) -> Modal<'a> {
+        let mut title = Text::new_with_text_and_coords(&font, title, (0, 0));
+        title.style.color = Color::white();
+        title.character_size = (2, "rem");
+        let lines = lines
+            .into_iter()
+            .map(|line| {
+                let mut line = Text::new_with_text_and_coords(&font, line, (0, 0));
+                line.style.color = Color::white();
+                line.character_size = (FONT_SIZE, "px");
+                line
+            })
+            .collect();
+
+        Modal {
+            screen: (0.0, 0.0, 0.0, 0.0),
+            area: (0.0, 0.0, 0.0, 0.0),
+            title,
+            lines,
+            content: (0.0, 0.0, 0.0, 0.0),
+            buttons: buttons
+                .into_iter()
+                .map(|text| Button::new((0.0, 0.0), None, font, text))
+                .collect(),
+            choice: None,
+            state: WidgetState::default(),
+        }
+    }
+
+    pub fn take_choice(&mut self) -> Option<usize> {
+        self.choice.take()
+    }
+
+    // the height is given before the layout and the position is known after it
+    pub fn set_content_height(&mut self, height: f64) {
+        self.content.3 = height;
+    }
+
+    pub fn get_content_area(&self) -> Rect {
+        self.content
+    }
+}
+
+impl<'a> Widget for Modal<'a> {
+    // the whole screen since the modal blocks it
+    fn get_area(&self) -> Rect {
+        self.screen
+    }
+
+    fn set_area(&mut self, screen: Rect) {
+        self.screen = screen;
+
+        let mut items = vec![Item::fixed(TITLE_HEIGHT)];
+        items.extend(self.lines.iter().map(|_line| Item::fixed(LINE_HEIGHT)));
+        if self.content.3 > 0.0 {
+            items.push(Item::fixed(self.content.3));
+        }
+        items.extend(
+            self.buttons
+                .iter()
+                .map(|_button| Item::fixed(BUTTON_HEIGHT)),
+        );
+        let height = TITLE_HEIGHT
+            + LINE_HEIGHT * self.lines.len() as f64
+            + self.content.3
+            + BUTTON_HEIGHT * self.buttons.len() as f64
+            + SPACING * (items.len() - 1) as f64
+            + PADDING * 2.0;
+        let mut rows = Stack::new(Axis::Vertical, items);
+        rows.padding = PADDING;
+        rows.spacing = SPACING;
+
+        let mut stack = Stack::new(Axis::Vertical, vec![Item::fixed(height)]);
+        stack.anchor = Anchor::Center;
+        let column = stack.layout(screen)[0];
+        let box_width = Item::fixed(BOX_WIDTH).clamped(0.0, screen.2);
+        let mut stack = Stack::new(Axis::Horizontal, vec![box_width]);
+        stack.anchor = Anchor::Center;
+        self.area = stack.layout(column)[0];
+
+        let mut rows = rows.layout(self.area).into_iter();
+        if let Some(row) = rows.next() {
+            self.title.coords = (row.0 as usize, (row.1 + TITLE_HEIGHT * 0.6) as usize);
+        }
+        for (line, row) in self.lines.iter_mut().zip(&mut rows) {
+            line.coords = (row.0 as usize, (row.1 + LINE_HEIGHT * 0.75) as usize);
+        }
+        if self.content.3 > 0.0 {
+            self.content = rows.next().unwrap_or(self.content);
+        }
+        for (button, row) in self.buttons.iter_mut().zip(rows) {
+            button.set_area(row);
+        }
+    }
+
+    fn get_state(&self) -> WidgetState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: WidgetState) {
+        self.state = state;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> Response {
+        let mut buttons: Vec<&mut dyn Widget> = Vec::new();
+        for button in self.buttons.iter_mut() {
+            buttons.push(button);
+        }
+        match route_event(&mut buttons, event) {
+            Some((idx, Response::Clicked)) => {
+                self.choice = Some(idx);
+                Response::Clicked
+            }
+            _ => Response::Consumed,
+        }
+    }
+}
+
+impl<'a> Drawable for Modal<'a> {
+    fn draw_on_canvas(&self, mut canvas: &mut Canvas) {
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.set_fill_style(&JsValue::from_str("rgba(0, 0, 0, 0.6)"));
+        context.fill_rect(self.screen.0, self.screen.1, self.screen.2, self.screen.3);
+
+        BOX_STYLE.apply_on_canvas(&mut canvas);
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.begin_path();
+        context.stroke_rect(self.area.0, self.area.1, self.area.2, self.area.3);
+        context.set_fill_style(&JsValue::from_str("rgb(24, 28, 39)"));
+        context.fill_rect(self.area.0, self.area.1, self.area.2, self.area.3);
+        context.stroke();
+
+        canvas.draw(&self.title);
+        for line in &self.lines {
+            canvas.draw(line);
+        }
+        for button in &self.buttons {
+            canvas.draw(button);
+        }
+    }
+}
+
+impl<'a> InputTarget for Modal<'a> {
+    // the board below never gets the events
+    fn route_ui_event(&mut self, event: &UiEvent) -> Routed {
+        self.handle_event(event);
+        Routed::Consumed
+    }
+
+    fn get_selected_position(&self) -> Option<HexIndex> {
+        None
+    }
+}
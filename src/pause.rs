@@ -0,0 +1,77 @@
+use crate::{
+    i18n::tr, input::*, modal::Modal, scene::*, settings::Settings, touch::TouchInput,
+    widget::Widget,
+};
+use wasm_game_lib::graphics::canvas::*;
+use wasm_game_lib::inputs::event::Event;
+
+const RESUME: usize = 0;
+const SETTINGS: usize = 1;
+const MAIN_MENU: usize = 2;
+
+// drawn over the battle, which is not updated while it is open
+pub struct Pause<'a> {
+    modal: Modal<'a>,
+    input: InputHandler<'a>,
+}
+
+impl<'a> Pause<'a> {
+    pub fn new(context: &Context<'a>) -> Pause<'a> {
+        let buttons = vec![
+            tr("pause.resume").to_string(),
+            tr("menu.settings").to_string(),
+            tr("menu.main_menu").to_string(),
+        ];
+        Pause {
+            modal: Modal::new(
+                context.arial,
+                tr("pause.title").to_string(),
+                Vec::new(),
+                buttons,
+            ),
+            input: InputHandler::new(context.viewport, context.preferences),
+        }
+    }
+}
+
+impl<'a> Scene<'a> for Pause<'a> {
+    fn handle_event(&mut self, event: Event) {
+        self.input.handle_event(event, &mut self.modal);
+    }
+
+    fn handle_touch(&mut self, touch: TouchInput) {
+        self.input.handle_touch(touch, &mut self.modal);
+    }
+
+    fn update(&mut self, _delta: f64, context: &Context<'a>) -> Transition<'a> {
+        let intents = self.input.poll_intents();
+        if intents.contains(&Intent::Pause) || intents.contains(&Intent::Cancel) {
+            return Transition::Pop;
+        }
+        match self.modal.take_choice() {
+            Some(RESUME) => Transition::Pop,
+            Some(SETTINGS) => Transition::Push(Box::new(Settings::new(context))),
+            Some(MAIN_MENU) => Transition::Menu,
+            _ => Transition::None,
+        }
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let screen = (
+            0.0,
+            0.0,
+            canvas.get_width() as f64,
+            canvas.get_height() as f64,
+        );
+        self.modal.set_area(screen);
+        canvas.draw(&self.modal);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn translate(&mut self, context: &Context<'a>) {
+        *self = Pause::new(context);
+    }
+}
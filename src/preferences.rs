@@ -0,0 +1,229 @@
+use crate::{audio::Mixer, keybindings::Keybindings, storage::Storage, viewport::*};
+
+const STORAGE_KEY: &str = "settings";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Setting {
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    ScrollSpeed,
+    MarginRatio,
+    AnimationSpeed,
+}
+
+impl Setting {
+    // Fake iter over all the settings, in the order of the settings screen
+    pub fn iter() -> [Setting; 6] {
+        [
+            Setting::MasterVolume,
+            Setting::MusicVolume,
+            Setting::EffectsVolume,
+            Setting::ScrollSpeed,
+            Setting::MarginRatio,
+            Setting::AnimationSpeed,
+        ]
+    }
+
+    // they are saved so they must not be translated
+    fn get_name(self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "master_volume",
+            Setting::MusicVolume => "music_volume",
+            Setting::EffectsVolume => "effects_volume",
+            Setting::ScrollSpeed => "scroll_speed",
+            Setting::MarginRatio => "margin_ratio",
+            Setting::AnimationSpeed => "animation_speed",
+        }
+    }
+
+    // key of the translated name
+    pub fn get_label(self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "settings.master_volume",
+            Setting::MusicVolume => "settings.music_volume",
+            Setting::EffectsVolume => "settings.effects_volume",
+            Setting::ScrollSpeed => "settings.scroll_speed",
+            Setting::MarginRatio => "settings.margin_ratio",
+            Setting::AnimationSpeed => "settings.animation_speed",
+        }
+    }
+
+    // (min, max, step)
+    pub fn get_range(self) -> (f64, f64, f64) {
+        match self {
+            Setting::MasterVolume | Setting::MusicVolume | Setting::EffectsVolume => {
+                (0.0, 1.0, 0.05)
+            }
+            Setting::ScrollSpeed => (1.0, 10.0, 0.5),
+            Setting::MarginRatio => (0.1, 0.4, 0.05),
+            Setting::AnimationSpeed => (0.5, 3.0, 0.25),
+        }
+    }
+
+    pub fn get_mixer(self) -> Option<Mixer> {
+        match self {
+            Setting::MasterVolume => Some(Mixer::Master),
+            Setting::MusicVolume => Some(Mixer::Music),
+            Setting::EffectsVolume => Some(Mixer::Effects),
+            _ => None,
+        }
+    }
+}
+
+// everything the player can change in the settings screen
+#[derive(Clone, Debug)]
+pub struct Preferences {
+    pub master_volume: f64,
+    pub music_volume: f64,
+    pub effects_volume: f64,
+    pub muted: bool,            // the volumes are kept for when the sound is back
+    pub scroll_speed: f64,      // in pixels per fixed step
+    pub margin_ratio: f64,      // of the width of the screen
+    pub animation_speed: f64,   // 2 plays the animations twice as fast
+    pub locale: Option<String>, // the locale of the browser is used if none was chosen
+    pub keybindings: Keybindings,
+}
+
+impl Default for Preferences {
+    fn default() -> Preferences {
+        Preferences {
+            master_volume: 1.0,
+            music_volume: 0.5,
+            effects_volume: 1.0,
+            muted: false,
+            scroll_speed: 3.0,
+            margin_ratio: DEFAULT_MARGIN_RATIO,
+            animation_speed: 1.0,
+            locale: None,
+            keybindings: Keybindings::default(),
+        }
+    }
+}
+
+impl Preferences {
+    pub fn get(&self, setting: Setting) -> f64 {
+        match setting {
+            Setting::MasterVolume => self.master_volume,
+            Setting::MusicVolume => self.music_volume,
+            Setting::EffectsVolume => self.effects_volume,
+            Setting::ScrollSpeed => self.scroll_speed,
+            Setting::MarginRatio => self.margin_ratio,
+            Setting::AnimationSpeed => self.animation_speed,
+        }
+    }
+
+    // the value is clamped to the range of the setting
+    pub fn set(&mut self, setting: Setting, value: f64) {
+        let (min, max, _step) = setting.get_range();
+        let value = value.max(min).min(max);
+        match setting {
+            Setting::MasterVolume => self.master_volume = value,
+            Setting::MusicVolume => self.music_volume = value,
+            Setting::EffectsVolume => self.effects_volume = value,
+            Setting::ScrollSpeed => self.scroll_speed = value,
+            Setting::MarginRatio => self.margin_ratio = value,
+            Setting::AnimationSpeed => self.animation_speed = value,
+        }
+    }
+
+    // one "setting=value" line per setting, the keybindings are saved separately
+    pub fn serialize(&self) -> String {
+        let mut lines: Vec<String> = Setting::iter()
+            .iter()
+            .map(|setting| format!("{}={}", setting.get_name(), self.get(*setting)))
+            .collect();
+        lines.push(format!("muted={}", self.muted));
+        if let Some(locale) = &self.locale {
+            lines.push(format!("locale={}", locale));
+        }
+        lines.join("\n")
+    }
+
+    // invalid lines are ignored and settings missing from the data keep their default value
+    pub fn deserialize(data: &str) -> Preferences {
+        let mut preferences = Preferences::default();
+        for line in data.lines() {
+            let mut parts = line.splitn(2, '=');
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name.trim(), value.trim()),
+                _ => continue,
+            };
+            if name == "locale" {
+                preferences.locale = Some(value.to_string());
+                continue;
+            }
+            if name == "muted" {
+                preferences.muted = value == "true";
+                continue;
+            }
+            let setting = Setting::iter()
+                .iter()
+                .find(|setting| setting.get_name() == name)
+                .copied();
+            if let (Some(setting), Ok(value)) = (setting, value.parse::<f64>()) {
+                if value.is_finite() {
+                    preferences.set(setting, value);
+                }
+            }
+        }
+        preferences
+    }
+
+    pub fn load(storage: &dyn Storage) -> Preferences {
+        let mut preferences = match storage.get_item(STORAGE_KEY) {
+            Some(data) => Preferences::deserialize(&data),
+            None => Preferences::default(),
+        };
+        preferences.keybindings = Keybindings::load(storage);
+        preferences
+    }
+
+    pub fn save(&self, storage: &dyn Storage) {
+        if !storage.set_item(STORAGE_KEY, &self.serialize()) {
+            log!("failed to save settings");
+        }
+        self.keybindings.save(storage);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::keybindings::*;
+    use crate::storage::MemoryStorage;
+    use wasm_game_lib::inputs::keyboard::Key;
+
+    #[test]
+    fn persistence() {
+        let storage = MemoryStorage::default();
+        let preferences = Preferences::load(&storage);
+        assert_eq!(preferences.scroll_speed, 3.0);
+        assert_eq!(preferences.locale, None);
+
+        let mut preferences = Preferences::default();
+        preferences.set(Setting::ScrollSpeed, 5.5);
+        preferences.set(Setting::MusicVolume, 2.0);
+        preferences.locale = Some(String::from("fr"));
+        preferences.muted = true;
+        preferences
+            .keybindings
+            .bind(Command::Pause, Binding::new(Key::Escape, false));
+        preferences.save(&storage);
+
+        let loaded = Preferences::load(&storage);
+        assert_eq!(loaded.scroll_speed, 5.5);
+        assert_eq!(loaded.music_volume, 1.0);
+        assert_eq!(loaded.locale, Some(String::from("fr")));
+        assert!(loaded.muted);
+        assert_eq!(
+            loaded.keybindings.get_command(Key::Escape, false),
+            Some(Command::Pause)
+        );
+
+        let preferences =
+            Preferences::deserialize("animation_speed=100\ninvalid\nmargin_ratio=wide");
+        assert_eq!(preferences.animation_speed, 3.0);
+        assert_eq!(preferences.margin_ratio, DEFAULT_MARGIN_RATIO);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{idx::*, life::*, map::*, *};
+use crate::{actions::Attack, atlas::Atlas, idx::*, life::*, map::*, *};
 
 pub enum Previsualisation {
     Movement(Option<Vec<HexIndex>>),
@@ -32,82 +32,106 @@ const PUSH_ARROW_STYLE: LineStyle = LineStyle {
     size: 10.0,
 };
 
+const CANCELLED_PUSH_ARROW_STYLE: LineStyle = LineStyle {
+    cap: LineCap::Round,
+    color: Color {
+        alpha: 255,
+        red: 255,
+        green: 60,
+        blue: 30,
+    },
+    join: LineJoin::Miter,
+    size: 10.0,
+};
+
 impl PrevisualisationItem {
     pub fn draw_on_canvas(&self, mut canvas: &mut Canvas, data: &DrawingData) {
         match self {
-            PrevisualisationItem::PushArrow(direction, _cancelled) => {
-                if let Some(point2) = data.position.get_neighbour(direction) {
-                    let context = canvas.get_2d_canvas_rendering_context();
-                    context.begin_path();
-
-                    let (x, y) = data.position.get_canvas_coords();
-                    let (x, y) = match direction {
-                        Direction::TopLeft => (x - 35, y - 35),
-                        Direction::TopRight => (x + 35, y - 35),
-                        Direction::Right => (x + 50, y),
-                        Direction::BottomRight => (x + 35, y + 35),
-                        Direction::BottomLeft => (x - 35, y + 35),
-                        Direction::Left => (x - 50, y),
-                    };
-                    let (x, y) = Map::internal_coords_to_screen_coords(
-                        data.dimensions,
-                        data.margin,
-                        x as isize + 128,
-                        y as isize + 256,
-                    );
-                    context.move_to(x as f64, y as f64);
-
-                    let (x, y) = point2.get_canvas_coords();
-                    let (x, y) = match direction {
-                        Direction::TopLeft => (x + 35, y + 35),
-                        Direction::TopRight => (x - 35, y + 35),
-                        Direction::Right => (x - 50, y),
-                        Direction::BottomRight => (x - 35, y - 35),
-                        Direction::BottomLeft => (x + 35, y - 35),
-                        Direction::Left => (x + 50, y),
-                    };
-                    let (x, y) = Map::internal_coords_to_screen_coords(
-                        data.dimensions,
-                        data.margin,
-                        x as isize + 128,
-                        y as isize + 256,
-                    );
-                    context.line_to(x as f64, y as f64);
-
-                    // TODO fix strange arrows
-                    let (xo, yo) = match direction {
-                        Direction::TopLeft => (x as f64, y as f64 + 35.0 * data.factor),
-                        Direction::TopRight => (x as f64, y as f64 + 35.0 * data.factor),
-                        Direction::Right => {
-                            (x as f64 - 35.0 * data.factor, y as f64 - 35.0 * data.factor)
-                        }
-                        Direction::BottomRight => (x as f64, y as f64 - 35.0 * data.factor),
-                        Direction::BottomLeft => (x as f64, y as f64 - 35.0 * data.factor),
-                        Direction::Left => {
-                            (x as f64 + 35.0 * data.factor, y as f64 + 35.0 * data.factor)
-                        }
-                    };
-                    let (xo2, yo2) = match direction {
-                        Direction::TopLeft => (x as f64 + 35.0 * data.factor, y as f64),
-                        Direction::TopRight => (x as f64 - 35.0 * data.factor, y as f64),
-                        Direction::Right => {
-                            (x as f64 - 35.0 * data.factor, y as f64 + 35.0 * data.factor)
+            PrevisualisationItem::PushArrow(direction, cancelled) => {
+                let context = canvas.get_2d_canvas_rendering_context();
+                context.begin_path();
+
+                // the tiles on the edges are close to 0 so the coords are signed
+                let (x, y) = data.position.get_canvas_coords();
+                let (x, y) = (x as isize, y as isize);
+                let (x, y) = match direction {
+                    Direction::TopLeft => (x - 35, y - 35),
+                    Direction::TopRight => (x + 35, y - 35),
+                    Direction::Right => (x + 50, y),
+                    Direction::BottomRight => (x + 35, y + 35),
+                    Direction::BottomLeft => (x - 35, y + 35),
+                    Direction::Left => (x - 50, y),
+                };
+                let (x, y) = data.viewport.board_to_screen(x + 128, y + 256);
+                context.move_to(x as f64, y as f64);
+
+                let (x, y) = match data.position.get_neighbour(direction) {
+                    Some(point2) => {
+                        let (x, y) = point2.get_canvas_coords();
+                        let (x, y) = (x as isize, y as isize);
+                        match direction {
+                            Direction::TopLeft => (x + 35, y + 35),
+                            Direction::TopRight => (x - 35, y + 35),
+                            Direction::Right => (x - 50, y),
+                            Direction::BottomRight => (x - 35, y - 35),
+                            Direction::BottomLeft => (x + 35, y - 35),
+                            Direction::Left => (x + 50, y),
                         }
-                        Direction::BottomRight => (x as f64 - 35.0 * data.factor, y as f64),
-                        Direction::BottomLeft => (x as f64 + 35.0 * data.factor, y as f64),
-                        Direction::Left => {
-                            (x as f64 + 35.0 * data.factor, y as f64 - 35.0 * data.factor)
+                    }
+                    // blocked by the edge of the board, the arrow stops at the border of the tile
+                    None => {
+                        let (x, y) = data.position.get_canvas_coords();
+                        let (x, y) = (x as isize, y as isize);
+                        match direction {
+                            Direction::TopLeft => (x - 64, y - 96),
+                            Direction::TopRight => (x + 64, y - 96),
+                            Direction::Right => (x + 126, y),
+                            Direction::BottomRight => (x + 64, y + 96),
+                            Direction::BottomLeft => (x - 64, y + 96),
+                            Direction::Left => (x - 126, y),
                         }
-                    };
-                    context.move_to(xo, yo);
-                    context.line_to(x as f64, y as f64);
-                    context.move_to(xo2, yo2);
-                    context.line_to(x as f64, y as f64);
+                    }
+                };
+                let (x, y) = data.viewport.board_to_screen(x + 128, y + 256);
+                context.line_to(x as f64, y as f64);
 
-                    PUSH_ARROW_STYLE.apply_on_canvas(&mut canvas);
+                // TODO fix strange arrows
+                let (xo, yo) = match direction {
+                    Direction::TopLeft => (x as f64, y as f64 + 35.0 * data.factor),
+                    Direction::TopRight => (x as f64, y as f64 + 35.0 * data.factor),
+                    Direction::Right => {
+                        (x as f64 - 35.0 * data.factor, y as f64 - 35.0 * data.factor)
+                    }
+                    Direction::BottomRight => (x as f64, y as f64 - 35.0 * data.factor),
+                    Direction::BottomLeft => (x as f64, y as f64 - 35.0 * data.factor),
+                    Direction::Left => {
+                        (x as f64 + 35.0 * data.factor, y as f64 + 35.0 * data.factor)
+                    }
+                };
+                let (xo2, yo2) = match direction {
+                    Direction::TopLeft => (x as f64 + 35.0 * data.factor, y as f64),
+                    Direction::TopRight => (x as f64 - 35.0 * data.factor, y as f64),
+                    Direction::Right => {
+                        (x as f64 - 35.0 * data.factor, y as f64 + 35.0 * data.factor)
+                    }
+                    Direction::BottomRight => (x as f64 - 35.0 * data.factor, y as f64),
+                    Direction::BottomLeft => (x as f64 + 35.0 * data.factor, y as f64),
+                    Direction::Left => {
+                        (x as f64 + 35.0 * data.factor, y as f64 - 35.0 * data.factor)
+                    }
+                };
+                context.move_to(xo, yo);
+                context.line_to(x as f64, y as f64);
+                context.move_to(xo2, yo2);
+                context.line_to(x as f64, y as f64);
 
-                    canvas.get_2d_canvas_rendering_context().stroke();
+                if *cancelled {
+                    CANCELLED_PUSH_ARROW_STYLE.apply_on_canvas(&mut canvas);
+                } else {
+                    PUSH_ARROW_STYLE.apply_on_canvas(&mut canvas);
                 }
+
+                canvas.get_2d_canvas_rendering_context().stroke();
             }
             PrevisualisationItem::LongDistanceShoot(target) => {
                 let context = canvas.get_2d_canvas_rendering_context();
@@ -118,21 +142,15 @@ impl PrevisualisationItem {
                 context.set_line_dash(&JsValue::from(array)).unwrap();
 
                 let (x, y) = data.position.get_canvas_coords();
-                let (x, y) = Map::internal_coords_to_screen_coords(
-                    data.dimensions,
-                    data.margin,
-                    x as isize + 128,
-                    y as isize + 256,
-                );
+                let (x, y) = data
+                    .viewport
+                    .board_to_screen(x as isize + 128, y as isize + 256);
                 context.move_to(x as f64, y as f64);
 
                 let (x, y) = target.get_canvas_coords();
-                let (x, y) = Map::internal_coords_to_screen_coords(
-                    data.dimensions,
-                    data.margin,
-                    x as isize + 128,
-                    y as isize + 256,
-                );
+                let (x, y) = data
+                    .viewport
+                    .board_to_screen(x as isize + 128, y as isize + 256);
                 context.line_to(x as f64, y as f64);
 
                 PUSH_ARROW_STYLE.apply_on_canvas(&mut canvas);
@@ -149,3 +167,49 @@ impl PrevisualisationItem {
         }
     }
 }
+
+// draw the order in which a barbarian will act above its head
+// the icon of the telegraphed attack, next to the initiative
+pub fn draw_intent_icon(canvas: &mut Canvas, data: &DrawingData, icons: &Atlas, attack: &Attack) {
+    let (x, y) = data.position.get_canvas_coords();
+    let (x, y) = data
+        .viewport
+        .board_to_screen(x as isize + 168, y as isize + 80);
+    icons.draw(
+        canvas,
+        attack.get_icon_name(),
+        (x as f64, y as f64),
+        60.0 * data.factor,
+    );
+}
+
+pub fn draw_initiative(canvas: &mut Canvas, data: &DrawingData, order: usize) {
+    let (x, y) = data.position.get_canvas_coords();
+    let (x, y) = data
+        .viewport
+        .board_to_screen(x as isize + 128, y as isize + 140);
+
+    let context = canvas.get_2d_canvas_rendering_context();
+    context.begin_path();
+    context
+        .arc(
+            x as f64,
+            y as f64,
+            30.0 * data.factor,
+            0.0,
+            2.0 * std::f64::consts::PI,
+        )
+        .unwrap();
+    context.set_fill_style(&JsValue::from_str("rgb(24, 28, 39)"));
+    context.fill();
+
+    context.set_font(&format!("{}px Arial", (40.0 * data.factor) as usize));
+    context.set_text_align("center");
+    context.set_text_baseline("middle");
+    context.set_fill_style(&JsValue::from_str("rgb(255, 255, 30)"));
+    context
+        .fill_text(&order.to_string(), x as f64, y as f64)
+        .unwrap();
+    context.set_text_align("start");
+    context.set_text_baseline("alphabetic");
+}
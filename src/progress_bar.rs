@@ -31,14 +31,10 @@ impl ProgressBar {
         }
     }
 
-    pub fn inc(&mut self) -> bool {
-        if self.current < self.max {
-            self.current += 1;
-
-            self.max == self.current
-        } else {
-            true
-        }
+    // amount: weight of the step that has been completed
+    pub fn add(&mut self, amount: usize) -> bool {
+        self.current = (self.current + amount).min(self.max);
+        self.max == self.current
     }
 }
 
@@ -0,0 +1,78 @@
+use crate::{
+    battle::Battle, i18n::*, input::*, modal::Modal, scene::*, touch::TouchInput, units::Outcome,
+    widget::Widget,
+};
+use wasm_game_lib::graphics::canvas::*;
+use wasm_game_lib::inputs::event::Event;
+
+const PLAY_AGAIN: usize = 0;
+const MAIN_MENU: usize = 1;
+
+// drawn over the finished battle
+pub struct Results<'a> {
+    modal: Modal<'a>,
+    input: InputHandler<'a>,
+    scenario: usize, // played again with the button
+}
+
+impl<'a> Results<'a> {
+    pub fn new(
+        context: &Context<'a>,
+        outcome: Outcome,
+        turns: usize,
+        scenario: usize,
+    ) -> Results<'a> {
+        let title = match outcome {
+            Outcome::Victory => tr("results.victory"),
+            Outcome::Defeat => tr("results.defeat"),
+        };
+        let buttons = vec![
+            tr("results.play_again").to_string(),
+            tr("menu.main_menu").to_string(),
+        ];
+        Results {
+            modal: Modal::new(
+                context.arial,
+                title.to_string(),
+                vec![tr_args("results.turns", &[&turns])],
+                buttons,
+            ),
+            input: InputHandler::new(context.viewport, context.preferences),
+            scenario,
+        }
+    }
+}
+
+impl<'a> Scene<'a> for Results<'a> {
+    fn handle_event(&mut self, event: Event) {
+        self.input.handle_event(event, &mut self.modal);
+    }
+
+    fn handle_touch(&mut self, touch: TouchInput) {
+        self.input.handle_touch(touch, &mut self.modal);
+    }
+
+    fn update(&mut self, _delta: f64, context: &Context<'a>) -> Transition<'a> {
+        self.input.poll_intents();
+        match self.modal.take_choice() {
+            Some(PLAY_AGAIN) => Transition::Reset(Box::new(Battle::new(context, self.scenario))),
+            Some(MAIN_MENU) => Transition::Menu,
+            _ => Transition::None,
+        }
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let screen = (
+            0.0,
+            0.0,
+            canvas.get_width() as f64,
+            canvas.get_height() as f64,
+        );
+        self.modal.set_area(screen);
+        canvas.draw(&self.modal);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
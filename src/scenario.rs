@@ -0,0 +1,188 @@
+use crate::{actions::PushPhysics, units::UnitType};
+
+// used when the scenario file could not be loaded
+const BUILTIN_SCENARIOS: &str = "\
+[scenario.first_blood]
+3 archer
+4 scout
+5 knight
+6 barbarian
+35 barbarian_variant
+51 armored_barbarian
+42 barbarian_lord_of_death
+
+[scenario.ambush]
+chain_pushes
+rocks 20 29 40
+26 knight
+30 archer
+34 scout
+1 barbarian
+8 barbarian_variant
+52 barbarian
+59 armored_barbarian
+";
+
+const UNIT_TYPES: [UnitType; 7] = [
+    UnitType::Archer,
+    UnitType::Knight,
+    UnitType::Scout,
+    UnitType::Barbarian,
+    UnitType::BarbarianVariant,
+    UnitType::ArmoredBarbarian,
+    UnitType::BarbarianLordOfDeath,
+];
+
+// the units placed on the board when a battle starts
+pub struct Scenario {
+    pub name: String, // key of the translated name
+    pub units: Vec<(usize, UnitType)>,
+    pub rocks: Vec<usize>,
+    pub push_physics: PushPhysics,
+}
+
+// a line of a scenario
+enum Entry {
+    Unit(usize, UnitType),
+    Rocks(Vec<usize>),
+    ChainPushes,
+    EdgeDamage(isize),
+    HazardDamage(isize),
+}
+
+impl Entry {
+    fn parse(line: &str) -> Option<Entry> {
+        let mut parts = line.split_whitespace();
+        let first = parts.next()?;
+        let rest: Vec<&str> = parts.collect();
+        match (first, rest.as_slice()) {
+            ("chain_pushes", []) => Some(Entry::ChainPushes),
+            ("edge_damage", [damage]) => damage.parse().ok().map(Entry::EdgeDamage),
+            ("hazard_damage", [damage]) => damage.parse().ok().map(Entry::HazardDamage),
+            ("rocks", positions) => positions
+                .iter()
+                .map(|position| position.parse().ok())
+                .collect::<Option<Vec<usize>>>()
+                .map(Entry::Rocks),
+            (position, [name]) => {
+                let unit_type = UNIT_TYPES
+                    .iter()
+                    .find(|unit_type| unit_type.get_texture_name() == *name)?;
+                Some(Entry::Unit(position.parse().ok()?, unit_type.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Scenario {
+    // a "[name]" line starts a scenario, each following "position unit" line places a unit
+    // the units are named like their textures and invalid scenarios are skipped
+    // "rocks 12 13" places hazards, "chain_pushes", "edge_damage 2" and "hazard_damage 2" set
+    // the push rules
+    pub fn parse_all(data: &str) -> Vec<Scenario> {
+        let mut scenarios: Vec<Scenario> = Vec::new();
+        let mut valid = true;
+        for line in data.lines().map(|line| line.trim()) {
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                if !valid {
+                    scenarios.pop();
+                }
+                scenarios.push(Scenario {
+                    name: line[1..line.len() - 1].trim().to_string(),
+                    units: Vec::new(),
+                    rocks: Vec::new(),
+                    push_physics: PushPhysics::default(),
+                });
+                valid = true;
+                continue;
+            }
+
+            let scenario = match scenarios.last_mut() {
+                Some(scenario) => scenario,
+                None => continue,
+            };
+            match Entry::parse(line) {
+                Some(Entry::Unit(position, unit_type)) => {
+                    scenario.units.push((position, unit_type))
+                }
+                Some(Entry::Rocks(positions)) => scenario.rocks.extend(positions),
+                Some(Entry::ChainPushes) => scenario.push_physics.chain_pushes = true,
+                Some(Entry::EdgeDamage(damage)) => scenario.push_physics.edge_damage = damage,
+                Some(Entry::HazardDamage(damage)) => scenario.push_physics.hazard_damage = damage,
+                None => valid = false,
+            }
+        }
+        if !valid {
+            scenarios.pop();
+        }
+        scenarios.retain(|scenario| scenario.is_valid());
+        scenarios
+    }
+
+    // the builtin scenarios are played if the file contains none
+    pub fn load(data: &str) -> Vec<Scenario> {
+        let scenarios = Scenario::parse_all(data);
+        if scenarios.is_empty() {
+            log!("no scenario in the scenario file, using the builtin ones");
+            return Scenario::get_builtin();
+        }
+        scenarios
+    }
+
+    pub fn get_builtin() -> Vec<Scenario> {
+        Scenario::parse_all(BUILTIN_SCENARIOS)
+    }
+
+    fn is_valid(&self) -> bool {
+        let on_board = self.units.iter().enumerate().all(|(idx, (position, _))| {
+            *position < 61 && self.units[..idx].iter().all(|(p, _)| p != position)
+        }) && self.rocks.iter().all(|position| *position < 61);
+        // a battle starting with a side already empty would end immediately
+        on_board
+            && self.units.iter().any(|(_, u)| u.is_barbarian())
+            && self.units.iter().any(|(_, u)| !u.is_barbarian())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::i18n::CATALOGS;
+
+    #[test]
+    fn scenarios() {
+        let scenarios = Scenario::get_builtin();
+        assert_eq!(scenarios.len(), 2);
+        for scenario in &scenarios {
+            assert!(CATALOGS[0].get(&scenario.name).is_some());
+        }
+        assert_eq!(scenarios[1].units[0], (26, UnitType::Knight));
+        assert_eq!(scenarios[0].push_physics, PushPhysics::default());
+        assert!(scenarios[1].push_physics.chain_pushes);
+        assert_eq!(scenarios[1].rocks, vec![20, 29, 40]);
+
+        let scenarios = Scenario::parse_all(
+            "\
+[only_barbarians]
+1 barbarian
+[typo]
+1 knight
+2 barbarien
+[out_of_the_board]
+1 knight
+2 barbarian
+rocks 3 61
+[kept]
+edge_damage 3
+1 knight
+2 barbarian",
+        );
+        let names: Vec<&str> = scenarios.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["kept"]);
+        assert_eq!(scenarios[0].push_physics.edge_damage, 3);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    assets::*, atlas::Atlas, audio::*, i18n::get_catalog, menu::MainMenu, preferences::*,
+    scenario::Scenario, storage::Storage, touch::TouchInput, viewport::Viewport,
+};
+use std::cell::RefCell;
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::{canvas::*, font::Font};
+use wasm_game_lib::inputs::event::Event;
+
+const FADE_DURATION: f64 = 300.0; // in milliseconds
+
+// what every scene needs to build the other scenes
+pub struct Context<'a> {
+    pub textures: &'a TextureRegistry,
+    pub icons: &'a Atlas<'a>,
+    pub arial: &'a Font,
+    pub scenarios: &'a [Scenario],
+    pub viewport: &'a RefCell<Viewport>,
+    pub audio: &'a RefCell<AudioManager<HtmlBackend<'a>>>,
+    pub preferences: &'a RefCell<Preferences>,
+    pub storage: &'a dyn Storage,
+}
+
+impl<'a> Context<'a> {
+    // the other preferences are read where they are used
+    pub fn apply_preferences(&self) {
+        let preferences = self.preferences.borrow();
+        let mut audio = self.audio.borrow_mut();
+        for setting in Setting::iter().iter() {
+            if let Some(mixer) = setting.get_mixer() {
+                let volume = match mixer {
+                    Mixer::Master if preferences.muted => 0.0,
+                    _ => preferences.get(*setting),
+                };
+                audio.set_volume(mixer, volume);
+            }
+        }
+        self.viewport
+            .borrow_mut()
+            .set_margin_ratio(preferences.margin_ratio);
+    }
+}
+
+// only the scene on top of the stack gets the events and is updated
+pub trait Scene<'a> {
+    fn handle_event(&mut self, event: Event);
+    fn handle_touch(&mut self, touch: TouchInput);
+    // delta: time since the last update in milliseconds
+    fn update(&mut self, delta: f64, context: &Context<'a>) -> Transition<'a>;
+    fn draw(&mut self, canvas: &mut Canvas);
+
+    // the scenes below an overlay are drawn behind it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    // a battle left for the main menu can be continued from it
+    fn is_resumable(&self) -> bool {
+        false
+    }
+
+    // called on every scene when the language changes
+    fn translate(&mut self, _context: &Context<'a>) {}
+}
+
+pub enum Transition<'a> {
+    None,
+    Push(Box<dyn Scene<'a> + 'a>),
+    Pop,
+    Replace(Box<dyn Scene<'a> + 'a>), // the scene on top
+    Reset(Box<dyn Scene<'a> + 'a>),   // every scene, a suspended battle is dropped
+    Menu,                             // back to the main menu, the battle is suspended
+    Resume,                           // continue the suspended battle
+}
+
+pub struct SceneStack<'a> {
+    context: Context<'a>,
+    scenes: Vec<Box<dyn Scene<'a> + 'a>>,
+    suspended: Option<Box<dyn Scene<'a> + 'a>>,
+    fade: f64,            // remaining time of the transition
+    locale: &'static str, // of the texts of the scenes
+}
+
+impl<'a> SceneStack<'a> {
+    pub fn new(context: Context<'a>) -> SceneStack<'a> {
+        let menu = MainMenu::new(&context, false);
+        SceneStack {
+            context,
+            scenes: vec![Box::new(menu)],
+            suspended: None,
+            fade: FADE_DURATION,
+            locale: get_catalog().locale,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: Event) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.handle_event(event);
+        }
+    }
+
+    pub fn handle_touch(&mut self, touch: TouchInput) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.handle_touch(touch);
+        }
+    }
+
+    pub fn update(&mut self, delta: f64) {
+        self.fade = (self.fade - delta).max(0.0);
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.update(delta, &self.context),
+            None => return,
+        };
+        self.apply(transition);
+
+        if get_catalog().locale != self.locale {
+            self.locale = get_catalog().locale;
+            for scene in self.scenes.iter_mut().chain(self.suspended.iter_mut()) {
+                scene.translate(&self.context);
+            }
+        }
+    }
+
+    fn apply(&mut self, transition: Transition<'a>) {
+        match transition {
+            Transition::None => return,
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            }
+            Transition::Reset(scene) => {
+                self.suspended = None;
+                self.scenes = vec![scene];
+            }
+            Transition::Menu => {
+                // the scenes above the battle, such as the pause menu, are dropped
+                let bottom = self.scenes.drain(..).next();
+                self.suspended = bottom.filter(|scene| scene.is_resumable());
+                let menu = MainMenu::new(&self.context, self.suspended.is_some());
+                self.scenes.push(Box::new(menu));
+            }
+            Transition::Resume => {
+                if let Some(scene) = self.suspended.take() {
+                    self.scenes = vec![scene];
+                }
+            }
+        }
+
+        // overlays appear without fading the scene below them
+        if self.scenes.last().map(|scene| !scene.is_overlay()) == Some(true) {
+            self.fade = FADE_DURATION;
+        }
+    }
+
+    pub fn draw(&mut self, canvas: &mut Canvas) {
+        let first = self
+            .scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        for scene in self.scenes[first..].iter_mut() {
+            scene.draw(canvas);
+        }
+
+        if self.fade > 0.0 {
+            let (width, height) = (canvas.get_width() as f64, canvas.get_height() as f64);
+            let context = canvas.get_2d_canvas_rendering_context();
+            context.set_fill_style(&JsValue::from_str(&format!(
+                "rgba(0, 0, 0, {})",
+                self.fade / FADE_DURATION
+            )));
+            context.fill_rect(0.0, 0.0, width, height);
+        }
+    }
+}
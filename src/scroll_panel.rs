@@ -0,0 +1,157 @@
+use crate::{layout::Rect, widget::*};
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::canvas::*;
+use wasm_game_lib::graphics::drawable::*;
+
+const SCROLL_STEP: f64 = 40.0; // in pixels
+const SCROLLBAR_WIDTH: f64 = 6.0;
+
+// a viewport on content taller than the panel, the content is drawn by its owner
+pub struct ScrollPanel {
+    area: Rect,
+    content_height: f64,
+    offset: f64, // pixels of content hidden above the panel
+    state: WidgetState,
+}
+
+impl ScrollPanel {
+    pub fn new(area: Rect) -> ScrollPanel {
+        ScrollPanel {
+            area,
+            content_height: 0.0,
+            offset: 0.0,
+            state: WidgetState::default(),
+        }
+    }
+
+    fn get_max_offset(&self) -> f64 {
+        (self.content_height - self.area.3).max(0.0)
+    }
+
+    pub fn get_offset(&self) -> f64 {
+        self.offset
+    }
+
+    pub fn set_content_height(&mut self, content_height: f64) {
+        self.content_height = content_height;
+        self.scroll_by(0.0);
+    }
+
+    // returns true if the content moved
+    pub fn scroll_by(&mut self, delta: f64) -> bool {
+        let offset = (self.offset + delta).max(0.0).min(self.get_max_offset());
+        let moved = offset != self.offset;
+        self.offset = offset;
+        moved
+    }
+
+    pub fn is_scrolled_to_end(&self) -> bool {
+        self.offset >= self.get_max_offset()
+    }
+
+    pub fn scroll_to_end(&mut self) {
+        self.offset = self.get_max_offset();
+    }
+
+    // draw the content clipped to the panel, the closure gets the top left corner of the content
+    pub fn draw_content<F: FnOnce(&mut Canvas, (f64, f64))>(&self, canvas: &mut Canvas, draw: F) {
+        let (x, y, width, height) = self.area;
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.save();
+        context.begin_path();
+        context.rect(x, y, width, height);
+        context.clip();
+        draw(canvas, (x, y - self.offset));
+        canvas.get_2d_canvas_rendering_context().restore();
+        canvas.draw(self);
+    }
+}
+
+impl Widget for ScrollPanel {
+    fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+        self.scroll_by(0.0);
+    }
+
+    fn get_state(&self) -> WidgetState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: WidgetState) {
+        self.state = state;
+    }
+
+    // the panel consumes the scroll events even at its limits so the board does not zoom
+    fn handle_event(&mut self, event: &UiEvent) -> Response {
+        match *event {
+            UiEvent::Scroll(position, delta_y) if self.hit_test(position, 0.0) => {
+                if self.scroll_by(delta_y.signum() * SCROLL_STEP) {
+                    Response::Changed
+                } else {
+                    Response::Consumed
+                }
+            }
+            _ => update_state(self, event),
+        }
+    }
+}
+
+// only the scrollbar, see draw_content
+impl Drawable for ScrollPanel {
+    fn draw_on_canvas(&self, canvas: &mut Canvas) {
+        if self.content_height <= self.area.3 {
+            return;
+        }
+        let (x, y, width, height) = self.area;
+        let bar_height = height * height / self.content_height;
+        let bar_y = y + (height - bar_height) * self.offset / self.get_max_offset();
+
+        let context = canvas.get_2d_canvas_rendering_context();
+        if self.state.hovered {
+            context.set_fill_style(&JsValue::from_str("rgb(97, 105, 126)"));
+        } else {
+            context.set_fill_style(&JsValue::from_str("rgb(67, 75, 96)"));
+        }
+        context.fill_rect(
+            x + width - SCROLLBAR_WIDTH,
+            bar_y,
+            SCROLLBAR_WIDTH,
+            bar_height,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scrolling() {
+        let mut panel = ScrollPanel::new((0.0, 0.0, 100.0, 200.0));
+        panel.set_content_height(150.0);
+        let event = UiEvent::Scroll((50.0, 50.0), 1.0);
+        assert_eq!(panel.handle_event(&event), Response::Consumed);
+        assert_eq!(panel.get_offset(), 0.0);
+
+        panel.set_content_height(250.0);
+        assert_eq!(panel.handle_event(&event), Response::Changed);
+        assert_eq!(panel.handle_event(&event), Response::Changed);
+        assert_eq!(panel.get_offset(), 50.0);
+        let outside = UiEvent::Scroll((150.0, 50.0), -1.0);
+        assert_eq!(panel.handle_event(&outside), Response::Ignored);
+
+        // the offset follows the content when it shrinks
+        panel.set_content_height(220.0);
+        assert_eq!(panel.get_offset(), 20.0);
+        assert!(panel.is_scrolled_to_end());
+
+        panel.set_content_height(300.0);
+        assert!(!panel.is_scrolled_to_end());
+        panel.scroll_to_end();
+        assert_eq!(panel.get_offset(), 100.0);
+    }
+}
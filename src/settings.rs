@@ -0,0 +1,397 @@
+use crate::{
+    button::*, checkbox::Checkbox, i18n::*, idx::HexIndex, input::*, keybindings::*, layout::*,
+    modal::Modal, preferences::*, scene::*, scroll_panel::ScrollPanel, slider::Slider,
+    touch::TouchInput, widget::*,
+};
+use wasm_game_lib::graphics::{canvas::*, color::Color, font::Font, text::Text};
+use wasm_game_lib::inputs::{event::Event, keyboard::*};
+
+const LANGUAGE: usize = 0;
+const BACK: usize = 1;
+
+const FONT_SIZE: usize = 20;
+const LABEL_HEIGHT: f64 = 24.0;
+const SLIDER_HEIGHT: f64 = 30.0;
+const CHECKBOX_HEIGHT: f64 = 30.0;
+const ROW_SPACING: f64 = 10.0;
+const PANEL_HEIGHT: f64 = 260.0; // the rows scroll when they are taller
+
+enum Row<'a> {
+    Setting(Setting, Text<'a>, Slider),
+    Muted(Checkbox<'a>),
+    Heading(Text<'a>),
+    Binding(Command, Text<'a>, Button<'a>),
+}
+
+impl<'a> Row<'a> {
+    fn get_height(&self) -> f64 {
+        match self {
+            Row::Setting(..) => LABEL_HEIGHT + SLIDER_HEIGHT,
+            Row::Muted(_) => CHECKBOX_HEIGHT,
+            Row::Heading(_) => LABEL_HEIGHT,
+            Row::Binding(..) => BUTTON_HEIGHT,
+        }
+    }
+}
+
+// the rows are laid out in a scroll panel in the content area of the modal
+struct Panel<'a> {
+    modal: Modal<'a>,
+    scroll: ScrollPanel,
+    rows: Vec<Row<'a>>,
+    changed: bool,
+    waiting: Option<usize>, // row of the command waiting for a key
+    refresh: bool,          // the binding buttons need new texts
+}
+
+fn get_content_height(rows: &[Row]) -> f64 {
+    let height: f64 = rows.iter().map(|row| row.get_height()).sum();
+    height + ROW_SPACING * (rows.len().max(1) - 1) as f64
+}
+
+impl<'a> Panel<'a> {
+    fn layout(&mut self, screen: Rect) {
+        self.modal.set_area(screen);
+        let area = self.modal.get_content_area();
+        self.scroll.set_area(area);
+        let content_height = get_content_height(&self.rows);
+        self.scroll.set_content_height(content_height);
+
+        let items = self
+            .rows
+            .iter()
+            .map(|row| Item::fixed(row.get_height()))
+            .collect();
+        let mut stack = Stack::new(Axis::Vertical, items);
+        stack.spacing = ROW_SPACING;
+        let content = (
+            area.0,
+            area.1 - self.scroll.get_offset(),
+            area.2,
+            content_height,
+        );
+        for (row, area) in self.rows.iter_mut().zip(stack.layout(content)) {
+            match row {
+                Row::Setting(_setting, label, slider) => {
+                    place_label(label, (area.0, area.1, area.2, LABEL_HEIGHT));
+                    slider.set_area((area.0, area.1 + LABEL_HEIGHT, area.2, SLIDER_HEIGHT));
+                }
+                Row::Muted(checkbox) => checkbox.set_area(area),
+                Row::Heading(label) => place_label(label, area),
+                Row::Binding(_command, label, button) => {
+                    place_label(label, area);
+                    // the button is on the right of its label
+                    let width = button.get_area().2;
+                    let columns =
+                        Stack::new(Axis::Horizontal, vec![Item::fill(), Item::fixed(width)])
+                            .layout(area);
+                    button.set_coords((columns[1].0, columns[1].1));
+                }
+            }
+        }
+    }
+}
+
+// the label is vertically centered in the area
+fn place_label(label: &mut Text, area: Rect) {
+    label.coords = (
+        area.0 as usize,
+        (area.1 + area.3 / 2.0 + LABEL_HEIGHT / 4.0) as usize,
+    );
+}
+
+impl<'a> InputTarget for Panel<'a> {
+    // the board below never gets the events
+    fn route_ui_event(&mut self, event: &UiEvent) -> Routed {
+        if let UiEvent::Click(..) = event {
+            self.refresh = self.refresh || self.waiting.is_some();
+            self.waiting = None;
+        }
+        // the wheel scrolls the rows instead of moving the sliders
+        if let UiEvent::Scroll(..) = event {
+            if self.scroll.handle_event(event) != Response::Ignored {
+                return Routed::Consumed;
+            }
+        }
+
+        // the rows hidden by the panel cannot be hit
+        let position = match *event {
+            UiEvent::PointerMove(position, _) => Some(position),
+            UiEvent::Click(position, _) => Some(position),
+            UiEvent::Scroll(position, _) => Some(position),
+            UiEvent::PointerLeave => None,
+        };
+        let visible = position.map(|p| self.scroll.hit_test(p, 0.0)) != Some(false);
+        let row_event = if visible {
+            *event
+        } else {
+            UiEvent::PointerLeave
+        };
+
+        let mut widgets: Vec<&mut dyn Widget> = Vec::new();
+        for row in self.rows.iter_mut() {
+            match row {
+                Row::Setting(_setting, _label, slider) => widgets.push(slider),
+                Row::Muted(checkbox) => widgets.push(checkbox),
+                Row::Binding(_command, _label, button) => widgets.push(button),
+                Row::Heading(_label) => (),
+            }
+        }
+        let routed = route_event(&mut widgets, &row_event);
+        // widget indices skip the headings
+        let widget_rows: Vec<usize> = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_idx, row)| !matches!(row, Row::Heading(_)))
+            .map(|(idx, _row)| idx)
+            .collect();
+        match routed {
+            Some((_idx, Response::Changed)) if visible => self.changed = true,
+            Some((idx, Response::Clicked)) if visible => {
+                if let Row::Binding(..) = self.rows[widget_rows[idx]] {
+                    self.waiting = Some(widget_rows[idx]);
+                    self.refresh = true;
+                }
+            }
+            Some(_) if visible => (),
+            _ => {
+                self.modal.handle_event(event);
+            }
+        }
+        Routed::Consumed
+    }
+
+    fn get_selected_position(&self) -> Option<HexIndex> {
+        None
+    }
+}
+
+pub struct Settings<'a> {
+    panel: Panel<'a>,
+    input: InputHandler<'a>,
+    arial: &'a Font,
+    shift: bool,
+    captured: Option<Binding>, // key pressed while a command was waiting for one
+}
+
+impl<'a> Settings<'a> {
+    pub fn new(context: &Context<'a>) -> Settings<'a> {
+        let buttons = vec![
+            tr_args("settings.language", &[&tr("language.name")]),
+            tr("menu.back").to_string(),
+        ];
+        let mut modal = Modal::new(
+            context.arial,
+            tr("settings.title").to_string(),
+            Vec::new(),
+            buttons,
+        );
+
+        let preferences = context.preferences.borrow();
+        let mut rows = Vec::new();
+        for setting in Setting::iter().iter() {
+            let (min, max, step) = setting.get_range();
+            rows.push(Row::Setting(
+                *setting,
+                Settings::new_label(context.arial, tr(setting.get_label())),
+                Slider::new(
+                    (0.0, 0.0, 0.0, 0.0),
+                    (min, max),
+                    step,
+                    preferences.get(*setting),
+                ),
+            ));
+            // the sound can be muted without losing the volumes
+            if *setting == Setting::EffectsVolume {
+                let mut muted = Checkbox::new(
+                    (0.0, 0.0),
+                    0.0,
+                    context.arial,
+                    tr("settings.muted").to_string(),
+                );
+                muted.checked = preferences.muted;
+                rows.push(Row::Muted(muted));
+            }
+        }
+        rows.push(Row::Heading(Settings::new_label(
+            context.arial,
+            tr("settings.controls"),
+        )));
+        for command in Command::iter() {
+            rows.push(Row::Binding(
+                command,
+                Settings::new_label(context.arial, tr(command.get_label())),
+                Settings::new_binding_button(context.arial, String::new()),
+            ));
+        }
+
+        modal.set_content_height(get_content_height(&rows).min(PANEL_HEIGHT));
+
+        let mut settings = Settings {
+            panel: Panel {
+                modal,
+                scroll: ScrollPanel::new((0.0, 0.0, 0.0, 0.0)),
+                rows,
+                changed: false,
+                waiting: None,
+                refresh: false,
+            },
+            input: InputHandler::new(context.viewport, context.preferences),
+            arial: context.arial,
+            shift: false,
+            captured: None,
+        };
+        settings.refresh_bindings(&preferences.keybindings);
+        settings
+    }
+
+    fn new_label(font: &'a Font, text: &str) -> Text<'a> {
+        let mut label = Text::new_with_text_and_coords(font, text.to_string(), (0, 0));
+        label.style.color = Color::white();
+        label.character_size = (FONT_SIZE, "px");
+        label
+    }
+
+    fn new_binding_button(font: &'a Font, text: String) -> Button<'a> {
+        let mut button = Button::new((0.0, 0.0), None, font, text);
+        button.text.character_size = (FONT_SIZE, "px");
+        button
+    }
+
+    // the buttons are rebuilt so they are measured again
+    fn refresh_bindings(&mut self, keybindings: &Keybindings) {
+        let arial = self.arial;
+        let waiting = self.panel.waiting;
+        for (idx, row) in self.panel.rows.iter_mut().enumerate() {
+            if let Row::Binding(command, _label, button) = row {
+                let text = match keybindings.get_binding(*command) {
+                    _ if waiting == Some(idx) => tr("settings.press_key").to_string(),
+                    Some(binding) => binding.get_name(),
+                    None => tr("settings.unbound").to_string(),
+                };
+                *button = Settings::new_binding_button(arial, text);
+            }
+        }
+    }
+}
+
+impl<'a> Scene<'a> for Settings<'a> {
+    fn handle_event(&mut self, event: Event) {
+        // a command waiting for a key gets the next one that can be bound
+        if let Event::KeyboardEvent(KeyboardEvent::Down(key)) = event {
+            if self.panel.waiting.is_some() && Binding::is_bindable(key) {
+                self.captured = Some(Binding::new(key, self.shift));
+                return;
+            }
+        }
+        match event {
+            Event::KeyboardEvent(KeyboardEvent::Down(Key::Shift)) => self.shift = true,
+            Event::KeyboardEvent(KeyboardEvent::Up(Key::Shift)) => self.shift = false,
+            _ => (),
+        }
+        self.input.handle_event(event, &mut self.panel);
+    }
+
+    fn handle_touch(&mut self, touch: TouchInput) {
+        self.input.handle_touch(touch, &mut self.panel);
+    }
+
+    fn update(&mut self, _delta: f64, context: &Context<'a>) -> Transition<'a> {
+        if self.panel.changed {
+            self.panel.changed = false;
+            {
+                let mut preferences = context.preferences.borrow_mut();
+                for row in &self.panel.rows {
+                    match row {
+                        Row::Setting(setting, _label, slider) => {
+                            preferences.set(*setting, slider.get_value())
+                        }
+                        Row::Muted(checkbox) => preferences.muted = checkbox.checked,
+                        _ => (),
+                    }
+                }
+                preferences.save(context.storage);
+            }
+            context.apply_preferences();
+        }
+
+        if let Some(binding) = self.captured.take() {
+            if let Some(idx) = self.panel.waiting.take() {
+                if let Row::Binding(command, ..) = self.panel.rows[idx] {
+                    let mut preferences = context.preferences.borrow_mut();
+                    preferences.keybindings.bind(command, binding);
+                    preferences.save(context.storage);
+                }
+                self.panel.refresh = true;
+            }
+        }
+        if self.panel.refresh {
+            self.panel.refresh = false;
+            self.refresh_bindings(&context.preferences.borrow().keybindings);
+        }
+
+        if self.input.poll_intents().contains(&Intent::Cancel) {
+            return Transition::Pop;
+        }
+        match self.panel.modal.take_choice() {
+            Some(LANGUAGE) => {
+                // the scenes are translated by the stack once the locale changed
+                let current = get_catalog().locale;
+                let idx = CATALOGS
+                    .iter()
+                    .position(|catalog| catalog.locale == current)
+                    .unwrap_or(0);
+                let locale = CATALOGS[(idx + 1) % CATALOGS.len()].locale;
+                set_locale(locale);
+                let mut preferences = context.preferences.borrow_mut();
+                preferences.locale = Some(locale.to_string());
+                preferences.save(context.storage);
+                Transition::None
+            }
+            Some(BACK) => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let screen = (
+            0.0,
+            0.0,
+            canvas.get_width() as f64,
+            canvas.get_height() as f64,
+        );
+        self.panel.layout(screen);
+        canvas.draw(&self.panel.modal);
+        let rows = &self.panel.rows;
+        self.panel.scroll.draw_content(canvas, |canvas, _origin| {
+            for row in rows {
+                match row {
+                    Row::Setting(_setting, label, slider) => {
+                        canvas.draw(label);
+                        canvas.draw(slider);
+                    }
+                    Row::Muted(checkbox) => canvas.draw(checkbox),
+                    Row::Heading(label) => canvas.draw(label),
+                    Row::Binding(_command, label, button) => {
+                        canvas.draw(label);
+                        canvas.draw(button);
+                    }
+                }
+            }
+        });
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    // the scroll position is kept
+    fn translate(&mut self, context: &Context<'a>) {
+        let offset = self.panel.scroll.get_offset();
+        *self = Settings::new(context);
+        let content_height = get_content_height(&self.panel.rows);
+        self.panel.scroll.set_content_height(content_height);
+        self.panel.scroll.scroll_by(offset);
+    }
+}
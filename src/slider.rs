@@ -0,0 +1,156 @@
+use crate::{layout::Rect, widget::*};
+use std::f64::consts::PI;
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::canvas::*;
+use wasm_game_lib::graphics::drawable::*;
+
+const TRACK_HEIGHT: f64 = 6.0;
+const KNOB_RADIUS: f64 = 10.0;
+
+// a value chosen by dragging the knob, clicking the track or scrolling over it
+pub struct Slider {
+    area: Rect,
+    value: f64,
+    range: (f64, f64),
+    step: f64, // the value is always a multiple of it
+    state: WidgetState,
+}
+
+impl Slider {
+    pub fn new(area: Rect, range: (f64, f64), step: f64, value: f64) -> Slider {
+        let mut slider = Slider {
+            area,
+            value: range.0,
+            range,
+            step,
+            state: WidgetState::default(),
+        };
+        slider.set_value(value);
+        slider
+    }
+
+    pub fn get_value(&self) -> f64 {
+        self.value
+    }
+
+    // returns true if the value changed
+    pub fn set_value(&mut self, value: f64) -> bool {
+        let value = (value / self.step).round() * self.step;
+        let value = value.max(self.range.0).min(self.range.1);
+        let changed = value != self.value;
+        self.value = value;
+        changed
+    }
+
+    fn get_value_at(&self, x: f64) -> f64 {
+        let ratio = ((x - self.area.0) / self.area.2).max(0.0).min(1.0);
+        self.range.0 + ratio * (self.range.1 - self.range.0)
+    }
+
+    fn get_knob_x(&self) -> f64 {
+        let ratio = (self.value - self.range.0) / (self.range.1 - self.range.0);
+        self.area.0 + ratio * self.area.2
+    }
+}
+
+impl Widget for Slider {
+    fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+
+    fn get_state(&self) -> WidgetState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: WidgetState) {
+        self.state = state;
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> Response {
+        match *event {
+            UiEvent::Scroll(position, delta_y) if self.hit_test(position, 0.0) => {
+                let step = if delta_y < 0.0 { self.step } else { -self.step };
+                if self.set_value(self.value + step) {
+                    Response::Changed
+                } else {
+                    Response::Consumed
+                }
+            }
+            UiEvent::PointerMove((x, _), _) | UiEvent::Click((x, _), _) => {
+                let response = update_state(self, event);
+                let dragged = self.state.pressed || response == Response::Clicked;
+                if dragged && self.set_value(self.get_value_at(x)) {
+                    Response::Changed
+                } else {
+                    response
+                }
+            }
+            _ => update_state(self, event),
+        }
+    }
+}
+
+impl Drawable for Slider {
+    fn draw_on_canvas(&self, canvas: &mut Canvas) {
+        let (x, y, width, height) = self.area;
+        let center = y + height / 2.0;
+        let knob_x = self.get_knob_x();
+
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.set_fill_style(&JsValue::from_str("rgb(67, 75, 96)"));
+        context.fill_rect(x, center - TRACK_HEIGHT / 2.0, width, TRACK_HEIGHT);
+        context.set_fill_style(&JsValue::from_str("rgb(66, 135, 245)"));
+        context.fill_rect(x, center - TRACK_HEIGHT / 2.0, knob_x - x, TRACK_HEIGHT);
+
+        if self.state.pressed {
+            context.set_fill_style(&JsValue::from_str("rgb(200, 200, 200)"));
+        } else if self.state.hovered || self.state.focused {
+            context.set_fill_style(&JsValue::from_str("rgb(230, 230, 230)"));
+        } else {
+            context.set_fill_style(&JsValue::from_str("white"));
+        }
+        context.begin_path();
+        context
+            .arc(knob_x, center, KNOB_RADIUS, 0.0, 2.0 * PI)
+            .unwrap();
+        context.fill();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn values() {
+        let mut slider = Slider::new((100.0, 0.0, 200.0, 30.0), (0.0, 1.0), 0.1, 0.55);
+        assert!((slider.get_value() - 0.6).abs() < 1e-9);
+
+        let event = UiEvent::PointerMove((150.0, 15.0), true);
+        assert_eq!(slider.handle_event(&event), Response::Changed);
+        assert!((slider.get_value() - 0.3).abs() < 1e-9);
+        // clicks outside of the track are left to the widgets below
+        assert_eq!(
+            slider.handle_event(&UiEvent::Click((400.0, 15.0), 0.0)),
+            Response::Ignored
+        );
+        let event = UiEvent::PointerMove((299.0, 15.0), true);
+        assert_eq!(slider.handle_event(&event), Response::Changed);
+        assert!((slider.get_value() - 1.0).abs() < 1e-9);
+
+        // moving without pressing only hovers
+        let event = UiEvent::PointerMove((120.0, 15.0), false);
+        assert_eq!(slider.handle_event(&event), Response::Consumed);
+        assert!(slider.state.hovered);
+
+        let event = UiEvent::Scroll((120.0, 15.0), -1.0);
+        assert_eq!(slider.handle_event(&event), Response::Consumed);
+        let event = UiEvent::Scroll((120.0, 15.0), 1.0);
+        assert_eq!(slider.handle_event(&event), Response::Changed);
+        assert!((slider.get_value() - 0.9).abs() < 1e-9);
+    }
+}
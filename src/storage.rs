@@ -0,0 +1,50 @@
+#[cfg(test)]
+use std::{cell::RefCell, collections::HashMap};
+
+// where the settings are saved, the tests cannot use the browser
+pub trait Storage {
+    fn get_item(&self, key: &str) -> Option<String>;
+    // returns false if the item could not be saved
+    fn set_item(&self, key: &str, value: &str) -> bool;
+}
+
+pub struct LocalStorage;
+
+impl LocalStorage {
+    fn get_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+impl Storage for LocalStorage {
+    fn get_item(&self, key: &str) -> Option<String> {
+        LocalStorage::get_storage()?.get_item(key).ok()?
+    }
+
+    fn set_item(&self, key: &str, value: &str) -> bool {
+        match LocalStorage::get_storage() {
+            Some(storage) => storage.set_item(key, value).is_ok(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStorage {
+    items: RefCell<HashMap<String, String>>,
+}
+
+#[cfg(test)]
+impl Storage for MemoryStorage {
+    fn get_item(&self, key: &str) -> Option<String> {
+        self.items.borrow().get(key).cloned()
+    }
+
+    fn set_item(&self, key: &str, value: &str) -> bool {
+        self.items
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+        true
+    }
+}
@@ -1,4 +1,4 @@
-use crate::actions::Attack;
+use crate::{actions::Attack, atlas::Atlas, layout::Rect, widget::*};
 use std::cell::RefCell;
 use wasm_bindgen::JsValue;
 use wasm_game_lib::graphics::canvas::*;
@@ -6,7 +6,6 @@ use wasm_game_lib::graphics::color::*;
 use wasm_game_lib::graphics::drawable::*;
 use wasm_game_lib::graphics::font::*;
 use wasm_game_lib::graphics::text::*;
-use wasm_game_lib::inputs::mouse::{get_mouse_position, is_mouse_pressed};
 
 const BOX_STYLE: LineStyle = LineStyle {
     cap: LineCap::Round,
@@ -22,6 +21,16 @@ const BOX_STYLE: LineStyle = LineStyle {
 
 const MARGIN: usize = 10;
 const FONT_SIZE: usize = 20;
+const ICON_SIZE: usize = 32;
+
+// words: number of displayed words, a word too long for the box stays alone on its line
+fn break_before_last_word(end_line: &mut Vec<usize>, words: usize) {
+    if let Some(idx) = words.checked_sub(2) {
+        if !end_line.contains(&idx) {
+            end_line.push(idx);
+        }
+    }
+}
 
 pub struct TextBox<'a> {
     coords: (f64, f64),
@@ -29,6 +38,8 @@ pub struct TextBox<'a> {
     full_message: Vec<&'a str>,
     displayed_message: RefCell<(usize, Vec<usize>)>,
     displayed_text: RefCell<Text<'a>>,
+    icon: Option<(&'a Atlas<'a>, &'static str)>, // drawn on the left of the text
+    state: WidgetState,
 }
 
 impl<'a> TextBox<'a> {
@@ -50,9 +61,55 @@ impl<'a> TextBox<'a> {
             full_message: text.split(' ').collect(),
             displayed_message: RefCell::new((0, Vec::new())),
             displayed_text: RefCell::new(displayed_text),
+            icon: None,
+            state: WidgetState::default(),
+        }
+    }
+
+    // the title is displayed before the text, separated by a dash
+    pub fn new_with_title(
+        coords: (f64, f64),
+        width: usize,
+        font: &'a Font,
+        title: &'a str,
+        text: &'a str,
+    ) -> TextBox<'a> {
+        let mut textbox = TextBox::new(coords, width, font, text);
+        let mut full_message: Vec<&'a str> = title.split(' ').collect();
+        full_message.push("-");
+        full_message.append(&mut textbox.full_message);
+        textbox.full_message = full_message;
+        textbox
+    }
+
+    pub fn set_icon(&mut self, atlas: &'a Atlas<'a>, name: &'static str) {
+        self.icon = Some((atlas, name));
+        self.displayed_text.borrow_mut().coords.0 = self.coords.0 as usize + self.get_indent();
+        let width = *self.width.borrow();
+        self.set_width(width);
+    }
+
+    // space on the left of the text
+    fn get_indent(&self) -> usize {
+        match self.icon {
+            Some(_) => MARGIN * 2 + ICON_SIZE,
+            None => MARGIN,
         }
     }
 
+    fn get_box_height(&self) -> f64 {
+        let text_height = self.displayed_text.borrow().get_height();
+        let height = match self.icon {
+            Some(_) => text_height.max(ICON_SIZE),
+            None => text_height,
+        };
+        height as f64 + FONT_SIZE as f64 / 2.0
+    }
+
+    pub fn get_width(&self) -> usize {
+        *self.width.borrow()
+    }
+
     pub fn set_width(&mut self, width: usize) {
         self.displayed_text.borrow_mut().set_text(String::new());
         *self.displayed_message.borrow_mut() = (0, Vec::new());
@@ -63,7 +120,9 @@ impl<'a> TextBox<'a> {
         let width = *self.width.borrow() as f64;
 
         while self.displayed_message.borrow().0 < self.full_message.len() {
-            if self.displayed_text.borrow().get_width(&mut canvas) <= width - MARGIN as f64 * 2.0 {
+            if self.displayed_text.borrow().get_width(&mut canvas)
+                <= width - (MARGIN + self.get_indent()) as f64
+            {
                 let (words, end_line) = &mut *self.displayed_message.borrow_mut();
                 *words += 1;
                 let mut displayed_message = String::new();
@@ -81,9 +140,9 @@ impl<'a> TextBox<'a> {
 
                 if *words == self.full_message.len()
                     && self.displayed_text.borrow().get_width(&mut canvas)
-                        > width - MARGIN as f64 * 2.0
+                        > width - (MARGIN + self.get_indent()) as f64
                 {
-                    end_line.push(*words - 2);
+                    break_before_last_word(end_line, *words);
                     let mut displayed_message = String::new();
                     for (idx, word) in self.full_message.iter().enumerate() {
                         if idx < *words {
@@ -99,7 +158,7 @@ impl<'a> TextBox<'a> {
                 }
             } else {
                 let (words, end_line) = &mut *self.displayed_message.borrow_mut();
-                end_line.push(*words - 2);
+                break_before_last_word(end_line, *words);
                 *words += 1;
                 let mut displayed_message = String::new();
                 for (idx, word) in self.full_message.iter().enumerate() {
@@ -118,37 +177,45 @@ impl<'a> TextBox<'a> {
     }
 
     pub fn get_height(&self) -> usize {
-        self.displayed_text.borrow().get_height() + MARGIN * 2
+        match self.icon {
+            Some(_) => self.displayed_text.borrow().get_height().max(ICON_SIZE) + MARGIN * 2,
+            None => self.displayed_text.borrow().get_height() + MARGIN * 2,
+        }
     }
 
-    pub fn set_y(&mut self, y: usize) {
-        self.coords.1 = y as f64;
-        self.displayed_text.borrow_mut().coords.1 = y + MARGIN + FONT_SIZE / 2;
+    pub fn set_coords(&mut self, coords: (f64, f64)) {
+        self.coords = coords;
+        self.displayed_text.borrow_mut().coords = (
+            coords.0 as usize + self.get_indent(),
+            coords.1 as usize + MARGIN + FONT_SIZE / 2,
+        );
     }
+}
 
-    pub fn is_hover(&self) -> bool {
-        let mouse_position = get_mouse_position();
-        let mouse_position = (mouse_position.0 as f64, mouse_position.1 as f64);
-        let width = *self.width.borrow() as f64;
-
-        mouse_position.0 > self.coords.0
-            && mouse_position.0 < self.coords.0 + width
-            && mouse_position.1 > self.coords.1
-            && mouse_position.1 < self.coords.1 + self.get_height() as f64
+impl<'a> Widget for TextBox<'a> {
+    fn get_area(&self) -> Rect {
+        (
+            self.coords.0,
+            self.coords.1,
+            *self.width.borrow() as f64,
+            self.get_height() as f64,
+        )
     }
 
-    pub fn is_hover_with_mouse_position(&self, mouse_position: (u32, u32)) -> bool {
-        let mouse_position = (mouse_position.0 as f64, mouse_position.1 as f64);
-        let width = *self.width.borrow() as f64;
+    // the height depends on the wrapped text
+    fn set_area(&mut self, area: Rect) {
+        self.set_coords((area.0, area.1));
+        if self.get_width() != area.2 as usize {
+            self.set_width(area.2 as usize);
+        }
+    }
 
-        mouse_position.0 > self.coords.0
-            && mouse_position.0 < self.coords.0 + width
-            && mouse_position.1 > self.coords.1
-            && mouse_position.1 < self.coords.1 + self.get_height() as f64
+    fn get_state(&self) -> WidgetState {
+        self.state
     }
 
-    pub fn is_pressed(&self) -> bool {
-        self.is_hover() && is_mouse_pressed()
+    fn set_state(&mut self, state: WidgetState) {
+        self.state = state;
     }
 }
 
@@ -161,30 +228,30 @@ impl<'a> Drawable for TextBox<'a> {
         let context = canvas.get_2d_canvas_rendering_context();
 
         context.begin_path();
-        context.stroke_rect(
-            self.coords.0,
-            self.coords.1,
-            width,
-            self.displayed_text.borrow().get_height() as f64 + FONT_SIZE as f64 / 2.0,
-        );
+        context.stroke_rect(self.coords.0, self.coords.1, width, self.get_box_height());
 
-        if self.is_hover() {
-            if self.is_pressed() {
-                context.set_fill_style(&JsValue::from_str("rgb(54, 58, 99)"));
-            } else {
-                context.set_fill_style(&JsValue::from_str("rgb(44, 48, 79)"));
-            }
+        if self.state.pressed {
+            context.set_fill_style(&JsValue::from_str("rgb(54, 58, 99)"));
+        } else if self.state.hovered {
+            context.set_fill_style(&JsValue::from_str("rgb(44, 48, 79)"));
         } else {
             context.set_fill_style(&JsValue::from_str("rgb(24, 28, 39)"));
         }
 
-        context.fill_rect(
-            self.coords.0,
-            self.coords.1,
-            width,
-            self.displayed_text.borrow().get_height() as f64 + FONT_SIZE as f64 / 2.0,
-        );
+        context.fill_rect(self.coords.0, self.coords.1, width, self.get_box_height());
         context.stroke();
         canvas.draw(&*self.displayed_text.borrow());
+
+        if let Some((atlas, name)) = self.icon {
+            atlas.draw(
+                canvas,
+                name,
+                (
+                    self.coords.0 + MARGIN as f64,
+                    self.coords.1 + FONT_SIZE as f64 / 4.0,
+                ),
+                ICON_SIZE as f64,
+            );
+        }
     }
 }
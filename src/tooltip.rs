@@ -0,0 +1,180 @@
+use crate::{idx::HexIndex, info_panel::get_lines, input::Intent, map::*, units::*, viewport::*};
+use std::cell::RefCell;
+use wasm_bindgen::JsValue;
+use wasm_game_lib::graphics::{canvas::*, color::*, drawable::*, image::*};
+
+const BOX_STYLE: LineStyle = LineStyle {
+    cap: LineCap::Round,
+    join: LineJoin::Round,
+    size: 2.0,
+    color: Color {
+        alpha: 255,
+        red: 67,
+        green: 75,
+        blue: 96,
+    },
+};
+
+const DELAY: f64 = 600.0; // in milliseconds
+const MARGIN: f64 = 8.0;
+const FONT_SIZE: f64 = 14.0;
+const LINE_HEIGHT: f64 = 18.0;
+const MAX_WIDTH: f64 = 300.0;
+
+pub struct Tooltip<'a> {
+    viewport: &'a RefCell<Viewport>,
+    highlight: &'a Image,
+    hovered: Option<HexIndex>,
+    hover_duration: f64,
+    lines: Vec<String>,   // empty while the tooltip is hidden
+    hexes: Vec<HexIndex>, // hit by the telegraphed attack of the hovered barbarian
+}
+
+impl<'a> Tooltip<'a> {
+    pub fn new(highlight: &'a Image, viewport: &'a RefCell<Viewport>) -> Tooltip<'a> {
+        Tooltip {
+            viewport,
+            highlight,
+            hovered: None,
+            hover_duration: 0.0,
+            lines: Vec::new(),
+            hexes: Vec::new(),
+        }
+    }
+
+    fn hide(&mut self) {
+        self.hover_duration = 0.0;
+        self.lines.clear();
+        self.hexes.clear();
+    }
+
+    pub fn handle_intent(&mut self, intent: &Intent) {
+        match intent {
+            Intent::HoverHex(index) => {
+                if self.hovered != Some(*index) {
+                    self.hovered = Some(*index);
+                    self.hide();
+                }
+            }
+            Intent::HoverNone => {
+                self.hovered = None;
+                self.hide();
+            }
+            _ => self.hide(),
+        }
+    }
+
+    // delta: time since the last update in milliseconds
+    // returns true if the content of the tooltip changed
+    pub fn update(&mut self, delta: f64, units: &Units, map: &Map) -> bool {
+        let hovered = match self.hovered {
+            Some(hovered) => hovered,
+            None => return false,
+        };
+        self.hover_duration += delta;
+        if self.hover_duration < DELAY {
+            return false;
+        }
+
+        // the content is refreshed every frame because units can move under the tooltip
+        let tile = map[&hovered];
+        let mut lines = vec![tile.get_name().to_string()];
+        lines.extend(tile.get_rules().iter().map(|rule| rule.to_string()));
+        let mut hexes = Vec::new();
+        if let Some(unit) = units.get(&hovered) {
+            let order = units.get_barbarian_order(&hovered);
+            lines.push(unit.unit_type.get_name().to_string());
+            lines.append(&mut get_lines(unit, order));
+            if unit.unit_type.is_barbarian() {
+                hexes = units.get_telegraphed_hexes(&hovered);
+            }
+        }
+
+        let changed = lines != self.lines || hexes != self.hexes;
+        self.lines = lines;
+        self.hexes = hexes;
+        changed
+    }
+
+    // keep a box of the given size on the screen, next to the hex if possible
+    fn get_position(&self, hex: &HexIndex, size: (f64, f64)) -> (f64, f64) {
+        let viewport = self.viewport.borrow();
+        let (x, y) = hex.get_canvas_coords();
+        let (left, top) = viewport.board_to_screen(x as isize, y as isize + 128);
+        let (right, _) = viewport.board_to_screen(x as isize + 256, y as isize);
+        let screen = (viewport.dimensions.0 as f64, viewport.dimensions.1 as f64);
+
+        let x = if right as f64 + size.0 <= screen.0 {
+            right as f64
+        } else {
+            left as f64 - size.0
+        };
+        (
+            x.max(0.0).min((screen.0 - size.0).max(0.0)),
+            (top as f64).max(0.0).min((screen.1 - size.1).max(0.0)),
+        )
+    }
+}
+
+impl<'a> Drawable for Tooltip<'a> {
+    fn draw_on_canvas(&self, mut canvas: &mut Canvas) {
+        let hovered = match (self.hovered, self.lines.is_empty()) {
+            (Some(hovered), false) => hovered,
+            _ => return,
+        };
+
+        // highlight the hexes hit by the telegraphed attack
+        let viewport = *self.viewport.borrow();
+        let factor = viewport.get_factor();
+        let area = viewport.get_board_area();
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.save();
+        context.begin_path();
+        context.rect(area.0, area.1, area.2, area.3);
+        context.clip();
+        for hex in &self.hexes {
+            let (x, y) = viewport.hex_to_screen(hex);
+            context
+                .draw_image_with_html_image_element_and_dw_and_dh(
+                    self.highlight.get_html_element(),
+                    x as f64,
+                    y as f64,
+                    256.0 * factor,
+                    384.0 * factor,
+                )
+                .unwrap();
+        }
+        context.restore();
+
+        context.set_font(&format!("{}px Arial", FONT_SIZE));
+        let width = self
+            .lines
+            .iter()
+            .map(|line| context.measure_text(line).map(|m| m.width()).unwrap_or(0.0))
+            .fold(0.0, f64::max)
+            .min(MAX_WIDTH)
+            + MARGIN * 2.0;
+        let height = self.lines.len() as f64 * LINE_HEIGHT + MARGIN * 2.0;
+        let (x, y) = self.get_position(&hovered, (width, height));
+
+        BOX_STYLE.apply_on_canvas(&mut canvas);
+        let context = canvas.get_2d_canvas_rendering_context();
+        context.begin_path();
+        context.set_fill_style(&JsValue::from_str("rgba(24, 28, 39, 0.95)"));
+        context.fill_rect(x, y, width, height);
+        context.stroke_rect(x, y, width, height);
+        context.stroke();
+
+        context.set_fill_style(&JsValue::from_str("white"));
+        for (idx, line) in self.lines.iter().enumerate() {
+            context
+                .fill_text_with_max_width(
+                    line,
+                    x + MARGIN,
+                    y + MARGIN + (idx + 1) as f64 * LINE_HEIGHT - (LINE_HEIGHT - FONT_SIZE),
+                    MAX_WIDTH,
+                )
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{HtmlCanvasElement, TouchEvent};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TouchKind {
+    Start,
+    Move,
+    End,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TouchInput {
+    pub kind: TouchKind,
+    pub touches: Vec<(f64, f64)>, // fingers remaining on the screen after the event
+}
+
+// wasm_game_lib does not report touch events so they are listened on the canvas directly
+pub struct TouchListener {
+    inputs: Rc<RefCell<Vec<TouchInput>>>,
+    _closures: Vec<Closure<dyn FnMut(TouchEvent)>>,
+}
+
+impl TouchListener {
+    pub fn new(element: &HtmlCanvasElement) -> TouchListener {
+        let inputs = Rc::new(RefCell::new(Vec::new()));
+        let mut closures = Vec::new();
+
+        for (name, kind) in [
+            ("touchstart", TouchKind::Start),
+            ("touchmove", TouchKind::Move),
+            ("touchend", TouchKind::End),
+            ("touchcancel", TouchKind::End),
+        ]
+        .iter()
+        {
+            let inputs = Rc::clone(&inputs);
+            let kind = *kind;
+            let closure = Closure::wrap(Box::new(move |event: TouchEvent| {
+                // prevent the browser from scrolling and from emulating mouse events
+                event.prevent_default();
+                let list = event.touches();
+                let touches = (0..list.length())
+                    .filter_map(|i| list.get(i))
+                    .map(|t| (t.client_x() as f64, t.client_y() as f64))
+                    .collect();
+                inputs.borrow_mut().push(TouchInput { kind, touches });
+            }) as Box<dyn FnMut(TouchEvent)>);
+
+            if element
+                .add_event_listener_with_callback(name, closure.as_ref().unchecked_ref())
+                .is_err()
+            {
+                log!("failed to listen {} events", name);
+            }
+            closures.push(closure);
+        }
+
+        TouchListener {
+            inputs,
+            _closures: closures,
+        }
+    }
+
+    pub fn poll_inputs(&self) -> Vec<TouchInput> {
+        self.inputs.borrow_mut().split_off(0)
+    }
+}
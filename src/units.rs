@@ -1,15 +1,16 @@
 use crate::{
-    actions::*, button::*, idx::HexIndex, life::*, map::*, pathfinder::*, previsualisation::*,
-    textbox::*, *,
+    actions::*, animation::*, assets::*, atlas::*, button::*, combat_log::*, events::*, i18n::tr,
+    idx::HexIndex, info_panel::*, input::*, layout::*, life::*, map::*, pathfinder::*,
+    previsualisation::*, textbox::*, widget::*, *,
 };
 use arr_macro::arr;
 use std::{cell::RefCell, convert::TryInto};
 use wasm_bindgen::JsValue;
 use wasm_game_lib::graphics::{canvas::*, color::*, drawable::*, font::*, image::*};
 
-const UNIT_NUMBER: usize = 7;
+const MARGIN_PADDING: f64 = 10.0;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum UnitType {
     Archer,
     Knight,
@@ -21,15 +22,28 @@ pub enum UnitType {
 }
 
 impl UnitType {
-    pub fn get_texture_idx(&self) -> usize {
+    // name of the texture in the registry
+    pub fn get_texture_name(&self) -> &'static str {
         match self {
-            UnitType::Archer => 0,
-            UnitType::Knight => 1,
-            UnitType::Scout => 2,
-            UnitType::Barbarian => 3,
-            UnitType::BarbarianVariant => 4,
-            UnitType::ArmoredBarbarian => 5,
-            UnitType::BarbarianLordOfDeath => 6,
+            UnitType::Archer => "archer",
+            UnitType::Knight => "knight",
+            UnitType::Scout => "scout",
+            UnitType::Barbarian => "barbarian",
+            UnitType::BarbarianVariant => "barbarian_variant",
+            UnitType::ArmoredBarbarian => "armored_barbarian",
+            UnitType::BarbarianLordOfDeath => "barbarian_lord_of_death",
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            UnitType::Archer => tr("unit.archer"),
+            UnitType::Knight => tr("unit.knight"),
+            UnitType::Scout => tr("unit.scout"),
+            UnitType::Barbarian => tr("unit.barbarian"),
+            UnitType::BarbarianVariant => tr("unit.barbarian"),
+            UnitType::ArmoredBarbarian => tr("unit.armored_barbarian"),
+            UnitType::BarbarianLordOfDeath => tr("unit.barbarian_lord_of_death"),
         }
     }
 
@@ -45,6 +59,19 @@ impl UnitType {
         }
     }
 
+    // units with a higher initiative act first
+    pub fn initiative(&self) -> usize {
+        match self {
+            UnitType::Archer => 3,
+            UnitType::Knight => 2,
+            UnitType::Scout => 4,
+            UnitType::Barbarian => 2,
+            UnitType::BarbarianVariant => 3,
+            UnitType::ArmoredBarbarian => 1,
+            UnitType::BarbarianLordOfDeath => 0,
+        }
+    }
+
     pub fn is_barbarian(&self) -> bool {
         match self {
             UnitType::Archer | UnitType::Knight | UnitType::Scout => false,
@@ -56,7 +83,13 @@ impl UnitType {
     }
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Victory,
+    Defeat,
+}
+
+#[derive(PartialEq, Clone)]
 pub struct Unit {
     pub unit_type: UnitType,
     pub remaining_moves: usize,
@@ -94,20 +127,20 @@ impl Unit {
         &self,
         mut canvas: &mut Canvas,
         data: &DrawingData,
-        textures: [&Image; UNIT_NUMBER],
+        textures: &TextureRegistry,
     ) {
         let coords = data.position.get_canvas_coords();
-        let coords = Map::internal_coords_to_screen_coords(
-            data.dimensions,
-            data.margin,
-            coords.0 as isize + 50,
-            coords.1 as isize + 160,
+        let coords = data.viewport.board_to_screen(
+            coords.0 as isize + 50 + data.offset.0,
+            coords.1 as isize + 160 + data.offset.1,
         );
 
         canvas
             .context
             .draw_image_with_html_image_element_and_dw_and_dh(
-                textures[self.unit_type.get_texture_idx()].get_html_element(),
+                textures
+                    .get(Category::Unit, self.unit_type.get_texture_name())
+                    .get_html_element(),
                 coords.0 as f64,
                 coords.1 as f64,
                 150.0 * data.factor,
@@ -128,29 +161,39 @@ struct SelectedUnit<'a> {
 
 pub struct Units<'a> {
     units: [Option<Unit>; 61],
-    textures: [&'a Image; UNIT_NUMBER],
-    overground: [&'a Image; 2],
-    margin: usize,
+    textures: &'a TextureRegistry,
+    icons: &'a Atlas<'a>,
+    viewport: &'a RefCell<Viewport>,
     line_style: LineStyle,
     next_turn_button: Button<'a>,
+    pause_button: Button<'a>,
+    pub combat_log: CombatLog<'a>,
     selected_unit: Option<SelectedUnit<'a>>,
-    barbarian_actions: Vec<(HexIndex, PrevisualisationItem)>,
-    animation_frame: RefCell<u64>,
+    inspected_unit: Option<HexIndex>, // unit held on touch screens
+    panel_area: Option<Rect>,         // of the info panel, set by the layout
+    margin_changed: bool,             // a widget of the margin must be drawn again
+    barbarian_actions: Vec<(HexIndex, Vec<(HexIndex, PrevisualisationItem)>)>,
+    clock: f64,
+    pub animations: Timeline,
+    events: Vec<GameEvent>,
+    turn: usize,
 }
 
 impl<'a> Units<'a> {
     pub fn new(
-        textures: [&'a Image; UNIT_NUMBER],
-        overground: [&'a Image; 2],
-        margin: usize,
+        textures: &'a TextureRegistry,
+        icons: &'a Atlas<'a>,
+        viewport: &'a RefCell<Viewport>,
         arial: &'a Font,
     ) -> Units<'a> {
         Units {
             units: arr!(None;61),
             textures,
-            margin,
-            overground,
-            next_turn_button: Button::new((10.0, 10.0), None, &arial, String::from("Next turn")),
+            viewport,
+            icons,
+            next_turn_button: Units::new_button(arial, "button.next_turn"),
+            pause_button: Units::new_button(arial, "button.pause"),
+            combat_log: CombatLog::new(textures.get(Category::Overlay, "red"), viewport),
             line_style: LineStyle {
                 cap: LineCap::Round,
                 color: Color::new(66, 135, 245),
@@ -158,8 +201,14 @@ impl<'a> Units<'a> {
                 size: 14.0,
             },
             selected_unit: None,
+            inspected_unit: None,
+            panel_area: None,
+            margin_changed: false,
             barbarian_actions: Vec::new(),
-            animation_frame: RefCell::new(0),
+            clock: 0.0,
+            animations: Timeline::default(),
+            events: Vec::new(),
+            turn: 1,
         }
     }
 
@@ -175,31 +224,160 @@ impl<'a> Units<'a> {
         self.units[idx.get_index()] = unit;
     }
 
-    pub fn set_margin(&mut self, margin: usize) {
-        self.margin = margin;
-        if let Some(selected_unit) = &mut self.selected_unit {
-            selected_unit.action_textboxes.0.set_width(margin - 20);
-            selected_unit.action_textboxes.1.set_width(margin - 20);
+    pub fn update(&mut self, delta: f64) {
+        self.clock += delta;
+        self.animations.update(delta);
+    }
+
+    // the previsualised life losses blink
+    pub fn is_blinking(&self) -> bool {
+        let is_life_change = |(_position, item): &(HexIndex, PrevisualisationItem)| {
+            matches!(item, PrevisualisationItem::LifeChange(_))
+        };
+        let telegraphed = self
+            .barbarian_actions
+            .iter()
+            .any(|(_barbarian, consequences)| consequences.iter().any(is_life_change));
+        let previsualised = match &self.selected_unit {
+            Some(SelectedUnit {
+                previsualisation: Previsualisation::Action(_action, _targets, consequences),
+                ..
+            }) => consequences.iter().any(is_life_change),
+            _ => false,
+        };
+        telegraphed || previsualised
+    }
+
+    pub fn get_turn(&self) -> usize {
+        self.turn
+    }
+
+    // the battle ends when one of the sides has no unit left
+    pub fn get_outcome(&self) -> Option<Outcome> {
+        let units = self.units.iter().flatten();
+        let barbarians = units.clone().filter(|u| u.unit_type.is_barbarian()).count();
+        let others = units.count() - barbarians;
+        if others == 0 {
+            Some(Outcome::Defeat)
+        } else if barbarians == 0 {
+            Some(Outcome::Victory)
+        } else {
+            None
         }
     }
 
-    pub fn handle_resize_event(&mut self, canvas: &mut Canvas) {
-        let canvas_height = canvas.get_height() as usize;
+    // input is queued while the consequences of the last action are displayed
+    pub fn is_animating(&self) -> bool {
+        self.animations.is_playing() || !self.events.is_empty()
+    }
+
+    pub fn poll_events(&mut self) -> Vec<GameEvent> {
+        self.events.split_off(0)
+    }
+
+    // true if the pointer changed the widgets of the margin since the last call
+    pub fn poll_margin_changes(&mut self) -> bool {
+        std::mem::replace(&mut self.margin_changed, false)
+    }
+
+    // the unit described in the info panel
+    fn get_displayed_unit(&self) -> Option<HexIndex> {
+        self.inspected_unit
+            .or_else(|| self.get_selected_position())
+            .filter(|position| self.get(position).is_some())
+    }
+
+    // position of a barbarian in the resolution order of this turn, starting at 1
+    pub fn get_barbarian_order(&self, position: &HexIndex) -> Option<usize> {
+        self.barbarian_actions
+            .iter()
+            .position(|(barbarian, _consequences)| barbarian == position)
+            .map(|order| order + 1)
+    }
+
+    // the hexes affected by the telegraphed action of a barbarian
+    pub fn get_telegraphed_hexes(&self, position: &HexIndex) -> Vec<HexIndex> {
+        let mut hexes = Vec::new();
+        for (barbarian, consequences) in &self.barbarian_actions {
+            if barbarian == position {
+                for (hex, _consequence) in consequences {
+                    if !hexes.contains(hex) {
+                        hexes.push(*hex);
+                    }
+                }
+            }
+        }
+        hexes
+    }
+
+    // positions the widgets of the margin, the combat log fills the area left
+    pub fn layout_margin(&mut self, canvas: &mut Canvas) {
+        let viewport = *self.viewport.borrow();
+        let area = (
+            0.0,
+            0.0,
+            viewport.margin as f64,
+            viewport.dimensions.1 as f64,
+        );
+        let width = (viewport.margin as f64 - MARGIN_PADDING * 2.0).max(0.0) as usize;
+        let displayed_unit = self.get_displayed_unit().is_some();
+
+        let mut items = vec![Item::fixed(BUTTON_HEIGHT)];
+        if displayed_unit {
+            items.push(Item::fixed(PANEL_HEIGHT));
+        }
+        items.push(Item::fill()); // combat log
         if let Some(selected_unit) = &mut self.selected_unit {
-            selected_unit
-                .action_textboxes
-                .0
-                .set_y(canvas_height - selected_unit.action_textboxes.0.get_height());
-            selected_unit.action_textboxes.1.set_y(
-                canvas_height
-                    - selected_unit.action_textboxes.0.get_height()
-                    - selected_unit.action_textboxes.1.get_height(),
-            );
+            for textbox in [
+                &mut selected_unit.action_textboxes.0,
+                &mut selected_unit.action_textboxes.1,
+            ]
+            .iter_mut()
+            {
+                if textbox.get_width() != width {
+                    textbox.set_width(width);
+                }
+                textbox.init(canvas);
+                items.push(Item::fixed(textbox.get_height() as f64));
+            }
+        }
+
+        let mut stack = Stack::new(Axis::Vertical, items);
+        stack.padding = MARGIN_PADDING;
+        stack.spacing = MARGIN_PADDING;
+        let mut areas = stack.layout(area).into_iter();
+
+        if let Some(button_area) = areas.next() {
+            // the pause button is on the right of the next turn button
+            let pause_width = self.pause_button.get_area().2;
+            let row = Stack::new(
+                Axis::Horizontal,
+                vec![Item::fill(), Item::fixed(pause_width)],
+            )
+            .layout(button_area);
+            self.next_turn_button.set_coords((row[0].0, row[0].1));
+            self.pause_button.set_coords((row[1].0, row[1].1));
+        }
+        self.panel_area = if displayed_unit { areas.next() } else { None };
+        let log_area = areas.next().unwrap_or(area);
+        self.combat_log.layout(canvas, log_area);
+        if let Some(selected_unit) = &mut self.selected_unit {
+            for textbox in [
+                &mut selected_unit.action_textboxes.0,
+                &mut selected_unit.action_textboxes.1,
+            ]
+            .iter_mut()
+            {
+                if let Some(textbox_area) = areas.next() {
+                    textbox.set_coords((textbox_area.0, textbox_area.1));
+                }
+            }
         }
     }
 
     pub fn apply_barbarian_actions(&mut self, map: &mut Map) {
-        Attack::apply(self.barbarian_actions.split_off(0), map, &mut self.units)
+        Units::resolve_barbarian_actions(map, &mut self.units, &mut self.events);
+        self.barbarian_actions.clear();
     }
 
     pub fn make_ai_play(&mut self) {
@@ -213,85 +391,149 @@ impl<'a> Units<'a> {
         }
     }
 
-    pub fn update_barbarian_actions(&mut self, map: &Map) {
-        let mut consequences = Vec::new();
-        for (position, (action, directions)) in self
-            .units
+    pub fn get_barbarian_order(units: &[Option<Unit>; 61]) -> Vec<HexIndex> {
+        let mut barbarians: Vec<HexIndex> = units
             .iter()
             .enumerate()
-            .filter(|u| u.1.is_some())
-            .map(|u| (u.0, u.1.as_ref().unwrap()))
-            .filter(|u| u.1.barbarian_next_action.is_some())
-            .map(|u| (u.0, u.1.barbarian_next_action.as_ref().unwrap()))
-        {
-            let position: HexIndex = position.try_into().unwrap();
+            .filter(|(_i, u)| {
+                u.as_ref()
+                    .map(|u| u.barbarian_next_action.is_some())
+                    .unwrap_or(false)
+            })
+            .map(|(i, _u)| i.try_into().unwrap())
+            .collect();
+        barbarians.sort_by_key(|position| {
+            (
+                std::cmp::Reverse(
+                    units[position.get_index()]
+                        .as_ref()
+                        .unwrap()
+                        .unit_type
+                        .initiative(),
+                ),
+                position.get_index(),
+            )
+        });
+        barbarians
+    }
+
+    // apply the actions in initiative order, each one on the board left by the previous ones
+    pub fn resolve_barbarian_actions(
+        map: &Map,
+        units: &mut [Option<Unit>; 61],
+        events: &mut Vec<GameEvent>,
+    ) -> Vec<(HexIndex, Vec<(HexIndex, PrevisualisationItem)>)> {
+        let mut pending = Units::get_barbarian_order(units);
+        let mut resolved = Vec::new();
+
+        while !pending.is_empty() {
+            let position = pending.remove(0);
+            let (action, directions) = match units[position.get_index()]
+                .as_ref()
+                .and_then(|u| u.barbarian_next_action.clone())
+            {
+                Some(next_action) => next_action,
+                None => continue, // killed by a previous action
+            };
+
             let mut target = Some(position);
-            for direction in directions {
+            for direction in &directions {
                 if let Some(target2) = target {
-                    target = target2.get_neighbour(&direction);
+                    target = target2.get_neighbour(direction);
                 }
             }
+
             if let Some(target) = target {
-                consequences.append(&mut action.get_consequences(
-                    map,
-                    &self.units,
-                    &position,
-                    &target,
+                let consequences = action.get_consequences(map, units, &position, &target);
+                events.push(GameEvent::UnitAttacked(
+                    position,
+                    units[position.get_index()]
+                        .as_ref()
+                        .unwrap()
+                        .unit_type
+                        .clone(),
+                    action,
+                    target,
+                    units[target.get_index()]
+                        .as_ref()
+                        .map(|u| u.unit_type.clone()),
                 ));
+                let action_events = Attack::apply_on_units(&consequences, units);
+                Units::track_barbarians(&mut pending, &action_events);
+                events.extend(action_events);
+                resolved.push((position, consequences));
             }
         }
-        self.barbarian_actions = consequences;
-    }
-
-    pub fn handle_mouse_move(&mut self, map: &Map, x: u32, y: u32) {
-        let coords = map.screen_coords_to_internal_canvas_coords(x as usize, y as usize);
-        if let Some(index) = HexIndex::from_canvas_coords(coords) {
-            // get the tile hovered by the mouse
-            match self {
-                Units {
-                    selected_unit:
-                        Some(SelectedUnit {
-                            position,
-                            reachable_tiles,
-                            previsualisation: Previsualisation::Movement(previsualisation),
-                            ..
-                        }),
-                    ..
-                } => *previsualisation = find_route(&reachable_tiles, *position, index),
-                Units {
-                    selected_unit:
-                        Some(SelectedUnit {
-                            position,
-                            previsualisation:
-                                Previsualisation::Action(actions, targets, consequences),
-                            ..
-                        }),
-                    units,
-                    ..
-                } => {
-                    *consequences = if *actions && targets.contains(&index) {
-                        units[position.get_index()]
-                            .as_ref()
-                            .unwrap()
-                            .attacks
-                            .1
-                            .get_consequences(&map, units, position, &index)
-                    } else if targets.contains(&index) {
-                        units[position.get_index()]
-                            .as_ref()
-                            .unwrap()
-                            .attacks
-                            .0
-                            .get_consequences(&map, units, position, &index)
-                    } else {
-                        Vec::new()
-                    };
+
+        resolved
+    }
+
+    // follow the barbarians waiting for their turn through the events of an action
+    // the events are in the order they happened so a hex never holds two units
+    fn track_barbarians(pending: &mut Vec<HexIndex>, events: &[GameEvent]) {
+        for event in events {
+            match event {
+                GameEvent::UnitPushed(from, _unit_type, to, _direction) => {
+                    if let Some(barbarian) = pending.iter_mut().find(|p| *p == from) {
+                        *barbarian = *to;
+                    }
                 }
+                GameEvent::UnitDied(position, _unit_type) => pending.retain(|p| p != position),
                 _ => (),
             }
         }
     }
 
+    pub fn update_barbarian_actions(&mut self, map: &Map) {
+        let mut board = self.units.clone();
+        self.barbarian_actions = Units::resolve_barbarian_actions(map, &mut board, &mut Vec::new());
+    }
+
+    // update the previsualisation of the selected unit
+    pub fn hover_hex(&mut self, map: &Map, index: HexIndex) {
+        match self {
+            Units {
+                selected_unit:
+                    Some(SelectedUnit {
+                        position,
+                        reachable_tiles,
+                        previsualisation: Previsualisation::Movement(previsualisation),
+                        ..
+                    }),
+                ..
+            } => *previsualisation = find_route(&reachable_tiles, *position, index),
+            Units {
+                selected_unit:
+                    Some(SelectedUnit {
+                        position,
+                        previsualisation: Previsualisation::Action(actions, targets, consequences),
+                        ..
+                    }),
+                units,
+                ..
+            } => {
+                *consequences = if *actions && targets.contains(&index) {
+                    units[position.get_index()]
+                        .as_ref()
+                        .unwrap()
+                        .attacks
+                        .1
+                        .get_consequences(&map, units, position, &index)
+                } else if targets.contains(&index) {
+                    units[position.get_index()]
+                        .as_ref()
+                        .unwrap()
+                        .attacks
+                        .0
+                        .get_consequences(&map, units, position, &index)
+                } else {
+                    Vec::new()
+                };
+            }
+            _ => (),
+        }
+    }
+
     pub fn move_selected_unit(&mut self, to: &HexIndex) {
         if !self[&self.selected_unit.as_ref().unwrap().position]
             .unit_type
@@ -302,6 +544,16 @@ impl<'a> Units<'a> {
                 .take()
                 .unwrap();
             unit.remaining_moves -= selected_unit.reachable_tiles[to.get_index()].unwrap();
+            if selected_unit.position != *to {
+                let path = find_route(&selected_unit.reachable_tiles, selected_unit.position, *to)
+                    .unwrap_or_default();
+                self.events.push(GameEvent::UnitMoved(
+                    selected_unit.position,
+                    unit.unit_type.clone(),
+                    *to,
+                    path,
+                ));
+            }
             self.set(&to, Some(unit));
         }
         self.selected_unit = None;
@@ -313,9 +565,10 @@ impl<'a> Units<'a> {
             selected_unit:
                 Some(SelectedUnit {
                     position,
-                    previsualisation: Previsualisation::Action(_action, targets, consequences),
+                    previsualisation: Previsualisation::Action(action, targets, consequences),
                     ..
                 }),
+            events,
             ..
         } = self
         {
@@ -326,40 +579,60 @@ impl<'a> Units<'a> {
                     .unit_type
                     .is_barbarian()
             {
-                Attack::apply(consequences.split_off(0), &mut map, units);
-                units[position.get_index()]
-                    .as_mut()
-                    .unwrap()
-                    .action_remaining = false;
+                let attacker = units[position.get_index()].as_ref().unwrap();
+                events.push(GameEvent::UnitAttacked(
+                    *position,
+                    attacker.unit_type.clone(),
+                    if *action {
+                        attacker.attacks.1.clone()
+                    } else {
+                        attacker.attacks.0.clone()
+                    },
+                    *target,
+                    units[target.get_index()]
+                        .as_ref()
+                        .map(|u| u.unit_type.clone()),
+                ));
+                events.append(&mut Attack::apply(
+                    consequences.split_off(0),
+                    &mut map,
+                    units,
+                ));
+                if let Some(unit) = units[position.get_index()].as_mut() {
+                    unit.action_remaining = false;
+                }
             }
             self.selected_unit = None;
         }
     }
 
-    pub fn select_unit(
-        &mut self,
-        index: HexIndex,
-        mut canvas: &mut Canvas,
-        arial: &'a Font,
-        map: &Map,
-    ) {
-        let canvas_height = canvas.get_height() as usize;
-        let mut t1 = TextBox::new(
-            (10.0, 200.0),
-            self.margin - 20,
+    // the buttons are positioned by the layout of the margin
+    fn new_button(arial: &'a Font, key: &'static str) -> Button<'a> {
+        Button::new((10.0, 10.0), None, arial, tr(key).to_string())
+    }
+
+    // the action textboxes are positioned by the layout of the margin
+    fn new_action_textboxes(&self, index: HexIndex, arial: &'a Font) -> (TextBox<'a>, TextBox<'a>) {
+        let mut t1 = TextBox::new_with_title(
+            (MARGIN_PADDING, 0.0),
+            0,
             &arial,
+            self[&index].attacks.0.get_name(),
             self[&index].attacks.0.get_description(),
         );
-        let mut t2 = TextBox::new(
-            (10.0, 300.0),
-            self.margin - 20,
+        let mut t2 = TextBox::new_with_title(
+            (MARGIN_PADDING, 0.0),
+            0,
             &arial,
+            self[&index].attacks.1.get_name(),
             self[&index].attacks.1.get_description(),
         );
-        t1.init(&mut canvas);
-        t2.init(&mut canvas);
-        t2.set_y(canvas_height - t2.get_height());
-        t1.set_y(canvas_height - t2.get_height() - t1.get_height());
+        t1.set_icon(self.icons, self[&index].attacks.0.get_icon_name());
+        t2.set_icon(self.icons, self[&index].attacks.1.get_icon_name());
+        (t1, t2)
+    }
+
+    pub fn select_unit(&mut self, index: HexIndex, arial: &'a Font, map: &Map) {
         self.selected_unit = Some(SelectedUnit {
             position: index,
             previsualisation: Previsualisation::Movement(None),
@@ -369,94 +642,166 @@ impl<'a> Units<'a> {
                 index,
                 self[&index].get_remaining_moves(),
             ),
-            action_textboxes: (t1, t2),
+            action_textboxes: self.new_action_textboxes(index, arial),
         });
     }
 
-    pub fn action_selection(&mut self, mouse_position: (u32, u32), map: &Map) -> bool {
-        if let Some(selected_unit) = &self.selected_unit {
-            if selected_unit
-                .action_textboxes
-                .0
-                .is_hover_with_mouse_position(mouse_position)
-                && self[&self.selected_unit.as_ref().unwrap().position].action_remaining
-            {
-                let targets = self[&selected_unit.position]
-                    .attacks
-                    .0
-                    .get_potential_targets(&map, &self, &selected_unit.position);
-                self.selected_unit.as_mut().unwrap().previsualisation =
-                    Previsualisation::Action(false, targets, Vec::new());
-                true
-            } else if selected_unit
-                .action_textboxes
-                .1
-                .is_hover_with_mouse_position(mouse_position)
-                && self[&self.selected_unit.as_ref().unwrap().position].action_remaining
-            {
-                let targets = self[&selected_unit.position]
-                    .attacks
-                    .1
-                    .get_potential_targets(&map, &self, &selected_unit.position);
-                self.selected_unit.as_mut().unwrap().previsualisation =
-                    Previsualisation::Action(true, targets, Vec::new());
-                true
-            } else {
-                false
+    // rebuild the texts after a change of language
+    pub fn translate(&mut self, arial: &'a Font) {
+        self.next_turn_button = Units::new_button(arial, "button.next_turn");
+        self.pause_button = Units::new_button(arial, "button.pause");
+        if let Some(position) = self.selected_unit.as_ref().map(|s| s.position) {
+            let textboxes = self.new_action_textboxes(position, arial);
+            if let Some(selected_unit) = &mut self.selected_unit {
+                selected_unit.action_textboxes = textboxes;
             }
-        } else {
-            false
         }
     }
 
-    pub fn next_turn(&mut self, mouse_position: (u32, u32), map: &mut Map) -> bool {
-        if self
-            .next_turn_button
-            .is_hover_with_mouse_position(mouse_position)
-        {
-            for unit in self.units.iter_mut().filter_map(|u| u.as_mut()) {
-                unit.remaining_moves = unit.unit_type.moves_per_turn();
-                unit.action_remaining = true;
+    // action: false for the first attack, true for the second one
+    pub fn select_action(&mut self, action: bool, map: &Map) {
+        let position = match &self.selected_unit {
+            Some(selected_unit) if self[&selected_unit.position].action_remaining => {
+                selected_unit.position
             }
+            _ => return,
+        };
+        let attack = if action {
+            &self[&position].attacks.1
+        } else {
+            &self[&position].attacks.0
+        };
+        let targets = attack.get_potential_targets(&map, &self, &position);
+        self.selected_unit.as_mut().unwrap().previsualisation =
+            Previsualisation::Action(action, targets, Vec::new());
+    }
 
-            self.apply_barbarian_actions(map);
-            self.make_ai_play();
-            self.update_barbarian_actions(&map);
+    pub fn end_turn(&mut self, map: &mut Map) {
+        for unit in self.units.iter_mut().filter_map(|u| u.as_mut()) {
+            unit.remaining_moves = unit.unit_type.moves_per_turn();
+            unit.action_remaining = true;
+        }
 
-            true
-        } else {
-            false
+        self.apply_barbarian_actions(map);
+        self.make_ai_play();
+        self.update_barbarian_actions(&map);
+
+        self.turn += 1;
+        self.events.push(GameEvent::TurnStarted(self.turn));
+    }
+
+    pub fn deselect(&mut self) {
+        self.selected_unit = None;
+    }
+
+    // select the next or the previous player unit having remaining moves
+    pub fn cycle_units(&mut self, forward: bool, arial: &'a Font, map: &Map) {
+        let candidates: Vec<HexIndex> = self
+            .units
+            .iter()
+            .enumerate()
+            .filter(|(_i, u)| {
+                u.as_ref()
+                    .map(|u| !u.unit_type.is_barbarian() && u.remaining_moves > 0)
+                    .unwrap_or(false)
+            })
+            .map(|(i, _u)| i.try_into().unwrap())
+            .collect();
+        if candidates.is_empty() {
+            return;
         }
+
+        let current = self
+            .get_selected_position()
+            .and_then(|p| candidates.iter().position(|c| *c == p));
+        let next = match (current, forward) {
+            (Some(current), true) => (current + 1) % candidates.len(),
+            (Some(current), false) => (current + candidates.len() - 1) % candidates.len(),
+            (None, true) => 0,
+            (None, false) => candidates.len() - 1,
+        };
+
+        let position = candidates[next];
+        self.select_unit(position, arial, map);
+        self.viewport.borrow_mut().center_camera_on(&position);
     }
 
-    pub fn handle_mouse_click(
-        &mut self,
-        mut map: &mut Map,
-        x: u32,
-        y: u32,
-        arial: &'a Font,
-        canvas: &mut Canvas,
-    ) {
-        // get the tile hovered by the mouse
-        let coords = map.screen_coords_to_internal_canvas_coords(x as usize, y as usize);
-        if let Some(clicked_tile_idx) = HexIndex::from_canvas_coords(coords) {
-            if let Some(selected_unit) = &self.selected_unit {
-                if (self.get(&clicked_tile_idx).is_none()
-                    || clicked_tile_idx == selected_unit.position)
-                    && selected_unit.previsualisation.is_movement_some()
-                {
-                    self.move_selected_unit(&clicked_tile_idx);
-                    self.update_barbarian_actions(&map);
-                } else if let Previsualisation::Action(_, _, _) = &selected_unit.previsualisation {
-                    self.apply_action_of_selected_unit(&clicked_tile_idx, &mut map);
-                    self.update_barbarian_actions(&map);
-                }
-            } else if self.get(&clicked_tile_idx).is_some() {
-                self.select_unit(clicked_tile_idx, canvas, arial, map);
+    pub fn handle_intent(&mut self, intent: &Intent, map: &mut Map, arial: &'a Font) {
+        if !intent.is_immediate() {
+            self.inspected_unit = None;
+        }
+        match intent {
+            Intent::SelectHex(index) => self.select_hex(map, *index, arial),
+            Intent::InspectHex(index) => self.inspected_unit = Some(*index),
+            Intent::HoverHex(index) => self.hover_hex(map, *index),
+            Intent::ChooseAttack(action) => self.select_action(*action, map),
+            Intent::EndTurn => self.end_turn(map),
+            Intent::Cancel => self.deselect(),
+            Intent::CycleUnits(forward) => self.cycle_units(*forward, arial, map),
+            Intent::HoverNone | Intent::Pause => (),
+        }
+    }
+
+    pub fn select_hex(&mut self, mut map: &mut Map, clicked_tile_idx: HexIndex, arial: &'a Font) {
+        if let Some(selected_unit) = &self.selected_unit {
+            if (self.get(&clicked_tile_idx).is_none() || clicked_tile_idx == selected_unit.position)
+                && selected_unit.previsualisation.is_movement_some()
+            {
+                self.move_selected_unit(&clicked_tile_idx);
+                self.update_barbarian_actions(&map);
+            } else if let Previsualisation::Action(_, _, _) = &selected_unit.previsualisation {
+                self.apply_action_of_selected_unit(&clicked_tile_idx, &mut map);
+                self.update_barbarian_actions(&map);
+            }
+        } else if self.get(&clicked_tile_idx).is_some() {
+            self.select_unit(clicked_tile_idx, arial, map);
+        }
+    }
+}
+
+impl<'a> InputTarget for Units<'a> {
+    fn route_ui_event(&mut self, event: &UiEvent) -> Routed {
+        // exact hits are preferred to the enlarged areas of the neighbouring widgets
+        let mut events = vec![*event];
+        if let UiEvent::Click(position, padding) = *event {
+            if padding > 0.0 {
+                events.insert(0, UiEvent::Click(position, 0.0));
+            }
+        }
+
+        for event in events {
+            // in drawing order, the intents match the indexes of the widgets
+            let mut widgets: Vec<&mut dyn Widget> = Vec::new();
+            let mut intents = Vec::new();
+            if let Some(selected_unit) = &mut self.selected_unit {
+                widgets.push(&mut selected_unit.action_textboxes.0);
+                widgets.push(&mut selected_unit.action_textboxes.1);
+                intents.push(Intent::ChooseAttack(false));
+                intents.push(Intent::ChooseAttack(true));
+            }
+            widgets.push(&mut self.next_turn_button);
+            widgets.push(&mut self.pause_button);
+            intents.push(Intent::EndTurn);
+            intents.push(Intent::Pause);
+            // the log is never clicked so it needs no intent
+            widgets.push(&mut self.combat_log);
+
+            let states = get_states(&widgets);
+            let routed = route_event(&mut widgets, &event);
+            let changed = get_states(&widgets) != states
+                || routed.map(|(_idx, response)| response) == Some(Response::Changed);
+            self.margin_changed = self.margin_changed || changed;
+            match routed {
+                Some((idx, Response::Clicked)) => return Routed::Intent(intents[idx].clone()),
+                Some(_) => return Routed::Consumed,
+                None => (),
             }
-        } else if !self.action_selection((x, y), map) {
-            self.next_turn((x, y), &mut map);
         }
+        Routed::Ignored
+    }
+
+    fn get_selected_position(&self) -> Option<HexIndex> {
+        self.selected_unit.as_ref().map(|s| s.position)
     }
 }
 
@@ -474,156 +819,249 @@ impl<'a> std::ops::IndexMut<&HexIndex> for Units<'a> {
     }
 }
 
-impl<'a> Drawable for Units<'a> {
-    fn draw_on_canvas(&self, mut canvas: &mut Canvas) {
-        *self.animation_frame.borrow_mut() += 1;
-        let animation_frame: u64 = *self.animation_frame.borrow();
-        let dimensions = (canvas.get_width(), canvas.get_height());
-        let factor_width: f64 = (dimensions.0 as usize - self.margin) as f64 / CANVAS_WIDTH;
-        let factor_height = dimensions.1 as f64 / CANVAS_HEIGHT;
-        let factor = if factor_width < factor_height {
-            factor_width
-        } else {
-            factor_height
-        };
+// every layer of the board is clipped so nothing is drawn on the margin
+fn clip_to_board(canvas: &mut Canvas, viewport: &Viewport) {
+    let context = canvas.get_2d_canvas_rendering_context();
+    context.save();
+    context.begin_path();
+    let area = viewport.get_board_area();
+    context.rect(area.0, area.1, area.2, area.3);
+    context.clip();
+}
 
-        let drawing_data = DrawingData {
-            factor,
-            dimensions,
-            margin: self.margin,
-            position: &0.try_into().unwrap(),
-            animation_frame,
+// the units are drawn on several layers, see Compositor
+impl<'a> Units<'a> {
+    fn get_drawing_data<'b>(&self, position: &'b HexIndex) -> DrawingData<'b> {
+        let viewport = *self.viewport.borrow();
+        DrawingData {
+            factor: viewport.get_factor(),
+            viewport,
+            position,
+            clock: self.clock,
+            offset: (0, 0),
+        }
+    }
+
+    // hexes reachable by the selected unit, its path and the targets of its action
+    pub fn draw_highlights(&self, mut canvas: &mut Canvas) {
+        let selected_unit = match &self.selected_unit {
+            Some(selected_unit) => selected_unit,
+            None => return,
         };
+        let viewport = *self.viewport.borrow();
+        let factor = viewport.get_factor();
+        clip_to_board(canvas, &viewport);
+
+        if let Previsualisation::Action(_action, targets, _consequences) =
+            &selected_unit.previsualisation
+        {
+            for target in targets {
+                let (x, y) = viewport.hex_to_screen(&target);
+
+                canvas
+                    .get_2d_canvas_rendering_context()
+                    .draw_image_with_html_image_element_and_dw_and_dh(
+                        self.textures
+                            .get(Category::Overlay, "red")
+                            .get_html_element(),
+                        x as f64,
+                        y as f64,
+                        256.0 * factor,
+                        384.0 * factor,
+                    )
+                    .unwrap();
+            }
+        } else {
+            if let Previsualisation::Movement(Some(route)) = &selected_unit.previsualisation {
+                let context = canvas.get_2d_canvas_rendering_context();
+                context.begin_path();
+
+                let (x, y) = selected_unit.position.get_canvas_coords();
+                let (x, y) = viewport.board_to_screen(x as isize + 128, y as isize + 256);
+                context.move_to(x as f64, y as f64);
+
+                for tile in route {
+                    let (x, y) = tile.get_canvas_coords();
+                    let (x, y) = viewport.board_to_screen(x as isize + 128, y as isize + 256);
+
+                    context.line_to(x as f64, y as f64);
+                }
+
+                self.line_style.apply_on_canvas(&mut canvas);
+
+                canvas.get_2d_canvas_rendering_context().stroke();
+            }
+
+            for reachable_tile in selected_unit
+                .reachable_tiles
+                .iter()
+                .enumerate()
+                .filter(|v| v.1.is_none())
+                .map(|v| {
+                    let v: HexIndex = v.0.try_into().unwrap();
+                    v
+                })
+            {
+                let (x, y) = viewport.hex_to_screen(&reachable_tile);
+
+                canvas
+                    .get_2d_canvas_rendering_context()
+                    .draw_image_with_html_image_element_and_dw_and_dh(
+                        self.textures
+                            .get(Category::Overlay, "shadow")
+                            .get_html_element(),
+                        x as f64,
+                        y as f64,
+                        256.0 * factor,
+                        384.0 * factor,
+                    )
+                    .unwrap();
+            }
+        }
+
+        canvas.get_2d_canvas_rendering_context().restore();
+    }
+
+    // the units and their animations
+    pub fn draw_units(&self, mut canvas: &mut Canvas) {
+        let origin: HexIndex = 0.try_into().unwrap();
+        let drawing_data = self.get_drawing_data(&origin);
+        clip_to_board(canvas, &drawing_data.viewport);
 
-        // draw units
         for (idx, unit) in self.units.iter().enumerate().filter(|(_i, u)| u.is_some()) {
             let unit = unit.as_ref().unwrap();
+            let position: HexIndex = idx.try_into().unwrap();
+            if self.animations.is_flashing(&position) {
+                canvas
+                    .get_2d_canvas_rendering_context()
+                    .set_global_alpha(0.3);
+            }
             unit.draw_on_canvas(
                 &mut canvas,
                 &DrawingData {
-                    position: &idx.try_into().unwrap(),
+                    position: &position,
+                    offset: self.animations.get_unit_offset(&position),
                     ..drawing_data
                 },
                 self.textures,
             );
+            canvas
+                .get_2d_canvas_rendering_context()
+                .set_global_alpha(1.0);
         }
+        self.animations.draw_on_canvas(&mut canvas, &drawing_data);
+
+        canvas.get_2d_canvas_rendering_context().restore();
+    }
+
+    // actions of the barbarians and consequences of the action of the selected unit
+    pub fn draw_telegraphs(&self, mut canvas: &mut Canvas) {
+        let origin: HexIndex = 0.try_into().unwrap();
+        let drawing_data = self.get_drawing_data(&origin);
+        clip_to_board(canvas, &drawing_data.viewport);
 
-        // draw barbabarian actions
-        for (position, consequence) in &self.barbarian_actions {
-            consequence.draw_on_canvas(
+        for (order, (barbarian, consequences)) in self.barbarian_actions.iter().enumerate() {
+            for (position, consequence) in consequences {
+                consequence.draw_on_canvas(
+                    &mut canvas,
+                    &DrawingData {
+                        position: &position,
+                        ..drawing_data
+                    },
+                )
+            }
+            draw_initiative(
                 &mut canvas,
                 &DrawingData {
-                    position: &position,
+                    position: &barbarian,
                     ..drawing_data
                 },
-            )
-        }
-
-        // draw selected unit info
-        if let Some(selected_unit) = &self.selected_unit {
-            let canvas_width = canvas.get_width();
-            let canvas_height = canvas.get_height();
-
-            if let Previsualisation::Action(_action, targets, consequences) =
-                &selected_unit.previsualisation
+                order + 1,
+            );
+            if let Some(Some((attack, _directions))) = self
+                .get(barbarian)
+                .as_ref()
+                .map(|u| u.barbarian_next_action.as_ref())
             {
-                for target in targets {
-                    let (x, y) = target.get_canvas_coords();
-                    let (x, y) = Map::internal_coords_to_screen_coords(
-                        (canvas_width, canvas_height),
-                        self.margin,
-                        x as isize,
-                        y as isize,
-                    );
-
-                    canvas
-                        .get_2d_canvas_rendering_context()
-                        .draw_image_with_html_image_element_and_dw_and_dh(
-                            self.overground[1].get_html_element(),
-                            x as f64,
-                            y as f64,
-                            256.0 * factor,
-                            384.0 * factor,
-                        )
-                        .unwrap();
-                }
-
-                // draw consequences of the selected action
-                for (position, consequence) in consequences {
-                    consequence.draw_on_canvas(
-                        &mut canvas,
-                        &DrawingData {
-                            position,
-                            ..drawing_data
-                        },
-                    )
-                }
-            } else {
-                if let Previsualisation::Movement(Some(route)) = &selected_unit.previsualisation {
-                    let context = canvas.get_2d_canvas_rendering_context();
-                    context.begin_path();
-
-                    let (x, y) = selected_unit.position.get_canvas_coords();
-                    let (x, y) = Map::internal_coords_to_screen_coords(
-                        (canvas_width, canvas_height),
-                        self.margin,
-                        x as isize + 128,
-                        y as isize + 256,
-                    );
-                    context.move_to(x as f64, y as f64);
-
-                    for tile in route {
-                        let (x, y) = tile.get_canvas_coords();
-                        let (x, y) = Map::internal_coords_to_screen_coords(
-                            (canvas_width, canvas_height),
-                            self.margin,
-                            x as isize + 128,
-                            y as isize + 256,
-                        );
-
-                        context.line_to(x as f64, y as f64);
-                    }
-
-                    self.line_style.apply_on_canvas(&mut canvas);
-
-                    canvas.get_2d_canvas_rendering_context().stroke();
-                }
+                draw_intent_icon(
+                    &mut canvas,
+                    &DrawingData {
+                        position: &barbarian,
+                        ..drawing_data
+                    },
+                    self.icons,
+                    attack,
+                );
+            }
+        }
 
-                for reachable_tile in selected_unit
-                    .reachable_tiles
-                    .iter()
-                    .enumerate()
-                    .filter(|v| v.1.is_none())
-                    .map(|v| {
-                        let v: HexIndex = v.0.try_into().unwrap();
-                        v
-                    })
-                {
-                    let (x, y) = reachable_tile.get_canvas_coords();
-                    let (x, y) = Map::internal_coords_to_screen_coords(
-                        (canvas_width, canvas_height),
-                        self.margin,
-                        x as isize,
-                        y as isize,
-                    );
-
-                    canvas
-                        .get_2d_canvas_rendering_context()
-                        .draw_image_with_html_image_element_and_dw_and_dh(
-                            self.overground[0].get_html_element(),
-                            x as f64,
-                            y as f64,
-                            256.0 * factor,
-                            384.0 * factor,
-                        )
-                        .unwrap();
-                }
+        if let Some(SelectedUnit {
+            previsualisation: Previsualisation::Action(_action, _targets, consequences),
+            ..
+        }) = &self.selected_unit
+        {
+            for (position, consequence) in consequences {
+                consequence.draw_on_canvas(
+                    &mut canvas,
+                    &DrawingData {
+                        position,
+                        ..drawing_data
+                    },
+                )
             }
+        }
 
+        canvas.get_2d_canvas_rendering_context().restore();
+    }
+
+    // the widgets of the margin, see layout_margin
+    pub fn draw_margin(&self, mut canvas: &mut Canvas) {
+        if let Some(selected_unit) = &self.selected_unit {
             canvas.draw(&selected_unit.action_textboxes.0);
             canvas.draw(&selected_unit.action_textboxes.1);
         }
 
         canvas.draw(&self.next_turn_button);
+        canvas.draw(&self.pause_button);
+
+        if let (Some(position), Some(area)) = (self.get_displayed_unit(), self.panel_area) {
+            let order = self.get_barbarian_order(&position);
+            let unit = &self[&position];
+            draw_unit_info(
+                &mut canvas,
+                unit,
+                self.textures
+                    .get(Category::Unit, unit.unit_type.get_texture_name()),
+                (area.0, area.1),
+                area.2,
+                order,
+            );
+        }
+
+        canvas.draw(&self.combat_log);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tracking_barbarians() {
+        let hex = |index: usize| -> HexIndex { index.try_into().unwrap() };
+        let mut pending = vec![hex(27), hex(28), hex(29)];
+
+        // the barbarian in 28 is killed and the one in 27 is pushed in its hex
+        // then both remaining barbarians are pushed in a chain
+        Units::track_barbarians(
+            &mut pending,
+            &[
+                GameEvent::UnitDied(hex(28), UnitType::Barbarian),
+                GameEvent::UnitPushed(hex(27), UnitType::Barbarian, hex(28), Direction::Right),
+                GameEvent::UnitPushed(hex(29), UnitType::Barbarian, hex(30), Direction::Right),
+                GameEvent::UnitPushed(hex(28), UnitType::Barbarian, hex(29), Direction::Right),
+            ],
+        );
+        assert_eq!(pending, vec![hex(29), hex(30)]);
     }
 }
@@ -0,0 +1,202 @@
+use crate::{camera::*, idx::*, layout::*, map::*};
+
+const SMALL_SCREEN_SIZE: u32 = 700;
+pub const DEFAULT_MARGIN_RATIO: f64 = 0.2;
+const MARGIN_MIN: f64 = 120.0;
+const MARGIN_MAX: f64 = 400.0;
+
+// width of the margin on the left of the board
+fn get_margin(dimensions: (u32, u32), ratio: f64) -> usize {
+    let stack = Stack::new(
+        Axis::Horizontal,
+        vec![
+            Item::ratio(ratio).clamped(MARGIN_MIN, MARGIN_MAX),
+            Item::fill(),
+        ],
+    );
+    let areas = stack.layout((0.0, 0.0, dimensions.0 as f64, dimensions.1 as f64));
+    (areas[0].2 as usize).min(dimensions.0 as usize)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub margin: usize,
+    pub margin_ratio: f64, // of the width of the screen, clamped to a usable width
+    pub dimensions: (u32, u32),
+    pub camera: Camera,
+}
+
+impl Viewport {
+    pub fn new(dimensions: (u32, u32)) -> Viewport {
+        Viewport {
+            margin: get_margin(dimensions, DEFAULT_MARGIN_RATIO),
+            margin_ratio: DEFAULT_MARGIN_RATIO,
+            dimensions,
+            camera: Camera::default(),
+        }
+    }
+
+    pub fn resize(&mut self, dimensions: (u32, u32)) {
+        self.dimensions = dimensions;
+        self.margin = get_margin(dimensions, self.margin_ratio);
+        self.clamp_camera();
+    }
+
+    pub fn set_margin_ratio(&mut self, margin_ratio: f64) {
+        self.margin_ratio = margin_ratio;
+        self.resize(self.dimensions);
+    }
+
+    // scale of the board when the camera is not zoomed
+    pub fn get_fitting_factor(&self) -> f64 {
+        let factor_width = (self.dimensions.0 as usize - self.margin) as f64 / CANVAS_WIDTH;
+        let factor_height = self.dimensions.1 as f64 / CANVAS_HEIGHT;
+        if factor_width < factor_height {
+            factor_width
+        } else {
+            factor_height
+        }
+    }
+
+    pub fn get_factor(&self) -> f64 {
+        self.get_fitting_factor() * self.camera.zoom
+    }
+
+    // position of the board on the screen when the camera is not moved
+    pub fn get_origin(&self) -> (f64, f64) {
+        let factor = self.get_fitting_factor();
+        let remaining_width =
+            (self.dimensions.0 as usize - self.margin) as f64 - CANVAS_WIDTH * factor;
+        let remaining_height = self.dimensions.1 as f64 - CANVAS_HEIGHT * factor;
+
+        (
+            self.margin as f64 + remaining_width / 2.0,
+            remaining_height / 2.0,
+        )
+    }
+
+    // (x, y, width, height) of the screen area where the board is displayed
+    pub fn get_board_area(&self) -> (f64, f64, f64, f64) {
+        (
+            self.margin as f64,
+            0.0,
+            (self.dimensions.0 as usize - self.margin) as f64,
+            self.dimensions.1 as f64,
+        )
+    }
+
+    pub fn screen_to_board(&self, x: u32, y: u32) -> (isize, isize) {
+        let origin = self.get_origin();
+        let factor = self.get_factor();
+        (
+            ((x as f64 - origin.0 - self.camera.pan.0) / factor) as isize,
+            ((y as f64 - origin.1 - self.camera.pan.1) / factor) as isize,
+        )
+    }
+
+    pub fn board_to_screen(&self, x: isize, y: isize) -> (isize, isize) {
+        let origin = self.get_origin();
+        let factor = self.get_factor();
+        (
+            (x as f64 * factor + origin.0 + self.camera.pan.0) as isize,
+            (y as f64 * factor + origin.1 + self.camera.pan.1) as isize,
+        )
+    }
+
+    pub fn screen_to_hex(&self, x: u32, y: u32) -> Option<HexIndex> {
+        if x as usize <= self.margin {
+            return None;
+        }
+        HexIndex::from_canvas_coords(self.screen_to_board(x, y))
+    }
+
+    // the top left corner of the texture of the tile
+    pub fn hex_to_screen(&self, index: &HexIndex) -> (isize, isize) {
+        let (x, y) = index.get_canvas_coords();
+        self.board_to_screen(x as isize, y as isize)
+    }
+
+    // extra space around the buttons when they are touched, small screens need bigger targets
+    pub fn get_touch_padding(&self) -> f64 {
+        if self.dimensions.0.min(self.dimensions.1) < SMALL_SCREEN_SIZE {
+            20.0
+        } else {
+            8.0
+        }
+    }
+
+    // ratio: how much the zoom is multiplied, the point under the cursor does not move
+    pub fn zoom_camera_by(&mut self, cursor: (f64, f64), ratio: f64) {
+        let origin = self.get_origin();
+        let factor = self.get_fitting_factor();
+        self.camera.zoom_at(cursor, origin, factor, ratio);
+        self.clamp_camera();
+    }
+
+    pub fn zoom_camera(&mut self, cursor: (u32, u32), zoom_in: bool) {
+        let origin = self.get_origin();
+        let factor = self.get_fitting_factor();
+        let cursor = (cursor.0 as f64, cursor.1 as f64);
+        if zoom_in {
+            self.camera.zoom_in_at(cursor, origin, factor);
+        } else {
+            self.camera.zoom_out_at(cursor, origin, factor);
+        }
+        self.clamp_camera();
+    }
+
+    pub fn move_camera(&mut self, movement: (f64, f64)) {
+        self.camera.pan.0 += movement.0;
+        self.camera.pan.1 += movement.1;
+        self.clamp_camera();
+    }
+
+    pub fn center_camera_on(&mut self, index: &HexIndex) {
+        let origin = self.get_origin();
+        let factor = self.get_fitting_factor();
+        let area = self.get_board_area();
+        let (x, y) = index.get_canvas_coords();
+        self.camera.center_on(
+            (x as f64 + 128.0, y as f64 + 256.0),
+            origin,
+            factor,
+            (area.0 + area.2 / 2.0, area.1 + area.3 / 2.0),
+        );
+        self.clamp_camera();
+    }
+
+    pub fn clamp_camera(&mut self) {
+        let origin = self.get_origin();
+        let factor = self.get_fitting_factor();
+        let area = self.get_board_area();
+        self.camera.clamp(
+            origin,
+            (CANVAS_WIDTH * factor, CANVAS_HEIGHT * factor),
+            area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn conversions() {
+        let mut viewport = Viewport::new((1500, 800));
+        viewport.zoom_camera((900, 400), true);
+        viewport.move_camera((-20.0, 10.0));
+
+        let (x, y) = viewport.board_to_screen(1000, 600);
+        let (x, y) = viewport.screen_to_board(x as u32, y as u32);
+        assert!((x - 1000).abs() <= 2);
+        assert!((y - 600).abs() <= 2);
+
+        let index: HexIndex = 30.try_into().unwrap();
+        let (x, y) = index.get_canvas_coords();
+        let (x, y) = viewport.board_to_screen(x as isize + 128, y as isize + 256);
+        assert_eq!(viewport.screen_to_hex(x as u32, y as u32), Some(index));
+        assert_eq!(viewport.screen_to_hex(10, 10), None);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::layout::Rect;
+use wasm_game_lib::graphics::drawable::Drawable;
+
+// pointer events in screen coordinates, sent to the widgets before the board
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UiEvent {
+    PointerMove((f64, f64), bool), // position, true while the button or the finger is down
+    PointerLeave,                  // the finger was lifted
+    Click((f64, f64), f64),        // position, padding used to enlarge touch targets
+    Scroll((f64, f64), f64),       // position, vertical delta
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Response {
+    Ignored, // the event goes to the widgets below and then to the board
+    Consumed,
+    Clicked,
+    Changed, // the value of the widget was modified
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct WidgetState {
+    pub hovered: bool,
+    pub pressed: bool,
+    pub focused: bool,
+}
+
+pub trait Widget: Drawable {
+    fn get_area(&self) -> Rect;
+    fn set_area(&mut self, area: Rect);
+    fn get_state(&self) -> WidgetState;
+    fn set_state(&mut self, state: WidgetState);
+
+    // padding: extra space around the widget, used to enlarge touch targets
+    fn hit_test(&self, position: (f64, f64), padding: f64) -> bool {
+        let (x, y, width, height) = self.get_area();
+        position.0 > x - padding
+            && position.0 < x + width + padding
+            && position.1 > y - padding
+            && position.1 < y + height + padding
+    }
+
+    fn handle_event(&mut self, event: &UiEvent) -> Response {
+        update_state(self, event)
+    }
+}
+
+// hover, pressed and focus states shared by every widget
+pub fn update_state<W: Widget + ?Sized>(widget: &mut W, event: &UiEvent) -> Response {
+    let mut state = widget.get_state();
+    let response = match event {
+        UiEvent::PointerMove(position, pressed) => {
+            state.hovered = widget.hit_test(*position, 0.0);
+            state.pressed = state.hovered && *pressed;
+            if state.hovered {
+                Response::Consumed
+            } else {
+                Response::Ignored
+            }
+        }
+        UiEvent::PointerLeave => {
+            state.hovered = false;
+            state.pressed = false;
+            Response::Ignored
+        }
+        UiEvent::Click(position, padding) => {
+            state.pressed = false;
+            state.focused = widget.hit_test(*position, *padding);
+            if state.focused {
+                Response::Clicked
+            } else {
+                Response::Ignored
+            }
+        }
+        UiEvent::Scroll(_position, _delta) => Response::Ignored,
+    };
+    widget.set_state(state);
+    response
+}
+
+// compared before and after an event to know if the widgets must be drawn again
+pub fn get_states(widgets: &[&mut dyn Widget]) -> Vec<WidgetState> {
+    widgets.iter().map(|widget| widget.get_state()).collect()
+}
+
+// widgets are given in drawing order so the last one is on top and sees the event first
+// returns the index of the widget that consumed the event
+pub fn route_event(widgets: &mut [&mut dyn Widget], event: &UiEvent) -> Option<(usize, Response)> {
+    let mut consumer = None;
+    for idx in (0..widgets.len()).rev() {
+        if consumer.is_some() {
+            // the widgets below lose the pointer
+            let mut state = widgets[idx].get_state();
+            state.hovered = false;
+            state.pressed = false;
+            if let UiEvent::Click(_, _) = event {
+                state.focused = false;
+            }
+            widgets[idx].set_state(state);
+            continue;
+        }
+        let response = widgets[idx].handle_event(event);
+        if response != Response::Ignored {
+            consumer = Some((idx, response));
+        }
+    }
+    consumer
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_game_lib::graphics::canvas::Canvas;
+
+    struct Square {
+        area: Rect,
+        state: WidgetState,
+    }
+
+    impl Drawable for Square {
+        fn draw_on_canvas(&self, _canvas: &mut Canvas) {}
+    }
+
+    impl Widget for Square {
+        fn get_area(&self) -> Rect {
+            self.area
+        }
+
+        fn set_area(&mut self, area: Rect) {
+            self.area = area;
+        }
+
+        fn get_state(&self) -> WidgetState {
+            self.state
+        }
+
+        fn set_state(&mut self, state: WidgetState) {
+            self.state = state;
+        }
+    }
+
+    #[test]
+    fn routing() {
+        let mut below = Square {
+            area: (0.0, 0.0, 100.0, 100.0),
+            state: WidgetState::default(),
+        };
+        let mut above = Square {
+            area: (50.0, 50.0, 100.0, 100.0),
+            state: WidgetState::default(),
+        };
+
+        let event = UiEvent::PointerMove((75.0, 75.0), true);
+        assert_eq!(
+            route_event(&mut [&mut below, &mut above], &event),
+            Some((1, Response::Consumed))
+        );
+        assert!(above.state.hovered && above.state.pressed);
+        assert!(!below.state.hovered);
+
+        let event = UiEvent::Click((25.0, 25.0), 0.0);
+        assert_eq!(
+            route_event(&mut [&mut below, &mut above], &event),
+            Some((0, Response::Clicked))
+        );
+        assert!(below.state.focused && !above.state.focused);
+
+        // touch targets are enlarged by the padding
+        let event = UiEvent::Click((160.0, 160.0), 20.0);
+        assert_eq!(
+            route_event(&mut [&mut below, &mut above], &event),
+            Some((1, Response::Clicked))
+        );
+        let event = UiEvent::Click((300.0, 300.0), 20.0);
+        assert_eq!(route_event(&mut [&mut below, &mut above], &event), None);
+    }
+}